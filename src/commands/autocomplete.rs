@@ -2,6 +2,8 @@ use poise::SlashArgument;
 use serenity::all::{AutocompleteChoice, ResolvedOption, ResolvedValue};
 
 use crate::ApplicationContext;
+use crate::i18n::{FALLBACK_LANGUAGE, command_language, tr};
+use crate::locales::DISCORD_LOCALES;
 use crate::state::FormRef;
 
 pub async fn autocomplete_form(
@@ -58,4 +60,95 @@ pub async fn autocomplete_field(
     }
 
     vec![]
-}
\ No newline at end of file
+}
+
+pub async fn autocomplete_launcher(
+    ctx: ApplicationContext<'_>,
+    _partial: &str,
+) -> Vec<AutocompleteChoice> {
+    let Some(form_id) = find_value(ctx, "form").await else {
+        return vec![];
+    };
+    let form_ref = FormRef::new(ctx.guild_id().unwrap(), form_id);
+
+    let (form, launchers) = match (ctx.data.get_form(form_ref).await, ctx.data.get_launchers(form_ref).await) {
+        (Ok(Some(form)), Ok(launchers)) => (form, launchers),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("an error occurred fetching auto-complete values for buttons: {}", e);
+            return vec![];
+        }
+        _ => return vec![],
+    };
+
+    launchers.into_iter()
+        .map(|l| {
+            let name = format!("{} ({})", l.label.as_deref().unwrap_or(form.title()), l.message_id);
+            AutocompleteChoice::new(name, l.message_id.to_string())
+        })
+        .collect()
+}
+
+pub async fn autocomplete_panel(
+    ctx: ApplicationContext<'_>,
+    _partial: &str,
+) -> Vec<AutocompleteChoice> {
+    match ctx.data.get_panels(ctx.guild_id().unwrap()).await {
+        Ok(panels) => {
+            panels.into_iter().map(|p| AutocompleteChoice::new(p.title.clone(), p.id().to_string())).collect()
+        }
+        Err(e) => {
+            tracing::error!("an error occurred fetching auto-complete values for panels: {}", e);
+            vec![]
+        }
+    }
+}
+
+pub async fn autocomplete_failed(
+    ctx: ApplicationContext<'_>,
+    _partial: &str,
+) -> Vec<AutocompleteChoice> {
+    let guild_id = ctx.guild_id().unwrap();
+    let (failed, forms) = match (ctx.data.get_failed_submissions(guild_id).await, ctx.data.get_form_ids(guild_id).await) {
+        (Ok(failed), Ok(forms)) => (failed, forms),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("an error occurred fetching auto-complete values for failed submissions: {}", e);
+            return vec![];
+        }
+    };
+
+    let anonymous = tr(command_language(ctx).await.unwrap_or(FALLBACK_LANGUAGE), "anonymous-user");
+    failed.into_iter()
+        .take(25)
+        .map(|f| {
+            let submission = f.submission;
+            let title = forms.iter().find(|(id, _)| *id == submission.form_id).map_or("?", |(_, title)| title.as_str());
+            let user_name = if submission.anonymous { &anonymous } else { &submission.user_name };
+            let name = format!("{title} – {user_name} ({})", submission.submitted_at.format("%Y-%m-%d %H:%M"));
+            AutocompleteChoice::new(name, submission.id.to_string())
+        })
+        .collect()
+}
+
+pub async fn autocomplete_locale(
+    _ctx: ApplicationContext<'_>,
+    partial: &str,
+) -> Vec<AutocompleteChoice> {
+    let partial = partial.to_lowercase();
+    DISCORD_LOCALES.iter()
+        .filter(|(locale, name)| locale.to_lowercase().contains(&partial) || name.to_lowercase().contains(&partial))
+        .take(25)
+        .map(|(locale, name)| AutocompleteChoice::new(format!("{name} ({locale})"), *locale))
+        .collect()
+}
+
+pub async fn autocomplete_timezone(
+    _ctx: ApplicationContext<'_>,
+    partial: &str,
+) -> Vec<AutocompleteChoice> {
+    let partial = partial.to_lowercase();
+    chrono_tz::TZ_VARIANTS.iter()
+        .filter(|tz| tz.name().to_lowercase().contains(&partial))
+        .take(25)
+        .map(|tz| AutocompleteChoice::new(tz.name(), tz.name()))
+        .collect()
+}
@@ -0,0 +1,191 @@
+use poise::{ChoiceParameter, CreateReply};
+use poise::serenity_prelude::*;
+
+use crate::{ApplicationContext, Context, Error};
+use crate::errors::UserFriendlyError;
+use crate::launchers::{components, is_not_found};
+use crate::state::{FormRef, Launcher};
+
+use super::autocomplete::{autocomplete_form, autocomplete_launcher};
+use super::{get_form, say};
+
+#[derive(ChoiceParameter)]
+pub(super) enum ButtonColor {
+    Blurple,
+    Grey,
+    Green,
+    Red,
+}
+
+impl From<ButtonColor> for ButtonStyle {
+    fn from(value: ButtonColor) -> Self {
+        match value {
+            ButtonColor::Blurple => ButtonStyle::Primary,
+            ButtonColor::Grey => ButtonStyle::Secondary,
+            ButtonColor::Green => ButtonStyle::Success,
+            ButtonColor::Red => ButtonStyle::Danger,
+        }
+    }
+}
+
+#[derive(ChoiceParameter)]
+enum ButtonReset {
+    #[name = "Text (follow the title of the form)"]
+    Text,
+    Emoji,
+    Message,
+}
+
+/// Parses a message ID or a link to a message.
+fn parse_message_id(message: &str) -> Result<MessageId, Error> {
+    message.rsplit('/').next()
+        .and_then(|id| id.trim().parse().ok())
+        .ok_or_else(|| UserFriendlyError::new("invalid-message").into())
+}
+
+pub(super) fn parse_emoji(emoji: String) -> Result<ReactionType, Error> {
+    ReactionType::try_from(emoji).map_err(|_| UserFriendlyError::new("invalid-emoji").into())
+}
+
+/// Create a button for a form
+#[poise::command(slash_command, ephemeral)]
+pub async fn button(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to create a button for"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "Text for the button (defaults to the title of the form)"]
+    #[max_length = 80]
+    text: Option<String>,
+    #[description = "A string to send with the button"] message: Option<String>,
+    #[description = "The color of the button"] color: ButtonColor,
+    #[description = "An emoji for the button"] emoji: Option<String>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let form = get_form(ctx, form_ref).await?;
+
+    let mut launcher = Launcher {
+        channel_id: ctx.channel_id(),
+        message_id: MessageId::default(),
+        label: text,
+        style: color.into(),
+        emoji: emoji.map(parse_emoji).transpose()?,
+        content: message,
+    };
+
+    let mut create_message = CreateMessage::new()
+        .components(components(&launcher, &form));
+
+    if let Some(content) = &launcher.content {
+        create_message = create_message.content(content);
+    }
+
+    launcher.message_id = ctx.channel_id().send_message(ctx, create_message).await?.id;
+    ctx.data.save_launcher(form_ref, &launcher).await?;
+
+    say(ctx, "button-created").await?;
+
+    Ok(())
+}
+
+/// Manages the posted buttons of forms
+#[poise::command(slash_command, ephemeral, subcommands("list", "edit"))]
+pub async fn buttons(_ctx: Context<'_>) -> serenity::Result<(), Error> {
+    panic!("called root command")
+}
+
+/// Lists the posted buttons of a form
+#[poise::command(slash_command, ephemeral)]
+async fn list(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let form = get_form(ctx, form_ref).await?;
+    let launchers = ctx.data.get_launchers(form_ref).await?;
+
+    if launchers.is_empty() {
+        say(ctx, "no-buttons").await?;
+        return Ok(());
+    }
+
+    let description = launchers.iter()
+        .map(|l| format!("- {} ({})", l.message_id.link(l.channel_id, Some(form_ref.guild_id)), l.label.as_deref().unwrap_or(form.title())))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.send(CreateReply::default().embed(CreateEmbed::new().title(form.title()).description(description))).await?;
+
+    Ok(())
+}
+
+/// Edits a posted button of a form
+#[poise::command(slash_command, ephemeral)]
+async fn edit(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The button to edit (or a link to its message)"]
+    #[autocomplete = "autocomplete_launcher"]
+    button: String,
+    #[description = "New text for the button"]
+    #[max_length = 80]
+    text: Option<String>,
+    #[description = "New message to send with the button"] message: Option<String>,
+    #[description = "New color of the button"] color: Option<ButtonColor>,
+    #[description = "New emoji for the button"] emoji: Option<String>,
+    #[description = "Something to remove from the button"] reset: Option<ButtonReset>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let form = get_form(ctx, form_ref).await?;
+    let message_id = parse_message_id(&button)?;
+    let mut launcher = ctx.data.get_launcher(form_ref, message_id).await?
+        .ok_or_else(|| UserFriendlyError::new("unknown-button"))?;
+
+    match reset {
+        Some(ButtonReset::Text) => launcher.label = None,
+        Some(ButtonReset::Emoji) => launcher.emoji = None,
+        Some(ButtonReset::Message) => launcher.content = None,
+        None => {}
+    }
+
+    if let Some(text) = text {
+        launcher.label = Some(text);
+    }
+
+    if let Some(message) = message {
+        launcher.content = Some(message);
+    }
+
+    if let Some(color) = color {
+        launcher.style = color.into();
+    }
+
+    if let Some(emoji) = emoji {
+        launcher.emoji = Some(parse_emoji(emoji)?);
+    }
+
+    let edit_message = EditMessage::new()
+        .content(launcher.content.as_deref().unwrap_or_default())
+        .components(components(&launcher, &form));
+
+    match launcher.channel_id.edit_message(ctx, launcher.message_id, edit_message).await {
+        Ok(_) => {
+            ctx.data.save_launcher(form_ref, &launcher).await?;
+            say(ctx, "button-updated").await?;
+        }
+        Err(e) if is_not_found(&e) => {
+            ctx.data.delete_launcher(form_ref, launcher.message_id).await?;
+            say(ctx, "button-message-deleted").await?;
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(())
+}
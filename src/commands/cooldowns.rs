@@ -1,13 +1,63 @@
-use poise::serenity_prelude::Mentionable;
-use serenity::all::UserId;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use poise::{ChoiceParameter, CreateReply};
+use poise::serenity_prelude::{CreateEmbed, FormattedTimestamp, FormattedTimestampStyle, Mentionable};
+use serenity::all::{RoleId, UserId};
 
 use crate::{ApplicationContext, Context, Error};
-use crate::state::FormRef;
+use crate::errors::UserFriendlyError;
+use crate::i18n::{command_language, tr, tr_args};
+use crate::state::{CalendarCooldown, EMBED_DESCRIPTION_MAX_LENGTH, FormRef, ResetPeriod};
+
+use super::autocomplete::{autocomplete_form, autocomplete_timezone};
+use super::{get_form, parse_cooldown, say, say_args};
+
+#[derive(ChoiceParameter)]
+enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(ChoiceParameter)]
+enum Day {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
 
-use super::autocomplete::autocomplete_form;
+impl From<Day> for Weekday {
+    fn from(value: Day) -> Self {
+        match value {
+            Day::Monday => Weekday::Mon,
+            Day::Tuesday => Weekday::Tue,
+            Day::Wednesday => Weekday::Wed,
+            Day::Thursday => Weekday::Thu,
+            Day::Friday => Weekday::Fri,
+            Day::Saturday => Weekday::Sat,
+            Day::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+fn parse_time(time: String) -> Result<NaiveTime, Error> {
+    NaiveTime::parse_from_str(&time, "%H:%M")
+        .map_err(|_| UserFriendlyError::new("invalid-time").with_arg("time", time).into())
+}
+
+fn parse_timezone(timezone: String) -> Result<Tz, Error> {
+    Tz::from_str(&timezone).map_err(|_| UserFriendlyError::new("invalid-timezone").with_arg("timezone", timezone).into())
+}
 
 /// Manage cooldowns
-#[poise::command(slash_command, subcommands("clear_cooldown"))]
+#[poise::command(slash_command, subcommands("clear_cooldown", "list", "set", "clear_all", "clear_user", "bypass_add", "bypass_remove", "schedule"))]
 pub async fn cooldowns(_ctx: Context<'_>) -> serenity::Result<(), Error> {
     panic!("called root command")
 }
@@ -26,9 +76,200 @@ async fn clear_cooldown(
 ) -> serenity::Result<(), Error> {
     ctx.defer_ephemeral().await?;
     if ctx.data.clear_cooldown(form_ref, user_id).await? {
-        ctx.say(format!("Cooldown was cleared for {}", user_id.mention())).await?;
+        say_args(ctx, "cooldown-cleared", &[("user", &user_id.mention())]).await?;
     } else {
-        ctx.say(format!("{} was not on cooldown for this form", user_id.mention())).await?;
+        say_args(ctx, "cooldown-not-active", &[("user", &user_id.mention())]).await?;
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Lists users on cooldown for a form
+#[poise::command(slash_command, ephemeral)]
+async fn list(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to list cooldowns for"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let language = command_language(ctx).await?;
+    let form = get_form(ctx, form_ref).await?;
+    let cooldowns = ctx.data.list_cooldowns(form_ref).await?;
+
+    if cooldowns.is_empty() {
+        ctx.say(tr(language, "no-cooldowns")).await?;
+        return Ok(());
+    }
+
+    // Who is on cooldown tells who submitted an anonymous form
+    let anonymous = tr(language, "anonymous-user");
+    let mut description = String::new();
+    for (i, (user_id, remaining)) in cooldowns.iter().enumerate() {
+        let user = if form.anonymous { anonymous.clone() } else { user_id.mention().to_string() };
+        let line = format!("- {user}: {}\n", humantime::format_duration(*remaining));
+        let more = tr_args(language, "cooldowns-truncated", &[("count", &(cooldowns.len() - i))]);
+
+        if description.len() + line.len() + more.len() > EMBED_DESCRIPTION_MAX_LENGTH {
+            description += &more;
+            break;
+        }
+
+        description += &line;
+    }
+
+    ctx.send(CreateReply::default().embed(CreateEmbed::new().title(form.title()).description(description))).await?;
+    Ok(())
+}
+
+/// Puts a user on cooldown for a form
+#[poise::command(slash_command, ephemeral)]
+async fn set(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to set the cooldown for"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The user to put on cooldown"]
+    #[rename = "user"]
+    user_id: UserId,
+    #[description = "How long the user must wait before submitting (e.g. `15days 2min 2s`)"]
+    duration: String,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    get_form(ctx, form_ref).await?;
+    // Cooldowns are stored in whole seconds, and Redis refuses to expire a key after none
+    let duration = Duration::from_secs(parse_cooldown(duration)?.as_secs());
+    if duration.is_zero() {
+        return Err(UserFriendlyError::new("cooldown-too-short").into());
+    }
+    ctx.data.set_cooldown(form_ref, user_id, duration).await?;
+    say_args(ctx, "cooldown-set", &[("user", &user_id.mention()), ("duration", &humantime::format_duration(duration))]).await?;
+    Ok(())
+}
+
+/// Clears the cooldowns of every user for a form
+#[poise::command(slash_command, rename = "clear-all", ephemeral)]
+async fn clear_all(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to clear cooldowns for"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let count = ctx.data.clear_cooldowns(form_ref).await?;
+    say_args(ctx, "cooldowns-cleared", &[("count", &count)]).await?;
+    Ok(())
+}
+
+/// Clears the cooldowns of a user for every form
+#[poise::command(slash_command, rename = "clear-user", ephemeral)]
+async fn clear_user(
+    ctx: ApplicationContext<'_>,
+    #[description = "The user to clear cooldowns for"]
+    #[rename = "user"]
+    user_id: UserId,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let count = ctx.data.clear_user_cooldowns(ctx.guild_id().unwrap(), user_id).await?;
+    say_args(ctx, "user-cooldowns-cleared", &[("user", &user_id.mention()), ("count", &count)]).await?;
+    Ok(())
+}
+
+/// Lets members with a role skip the cooldown of a form
+#[poise::command(slash_command, rename = "bypass-add", ephemeral)]
+async fn bypass_add(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to modify"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The role which skips the cooldown"]
+    #[rename = "role"]
+    role_id: RoleId,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    if !form.add_cooldown_bypass_role(role_id) {
+        say_args(ctx, "cooldown-bypass-exists", &[("role", &role_id.mention())]).await?;
+        return Ok(());
+    }
+
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say_args(ctx, "cooldown-bypass-added", &[("role", &role_id.mention())]).await?;
+    Ok(())
+}
+
+/// Stops a role from skipping the cooldown of a form
+#[poise::command(slash_command, rename = "bypass-remove", ephemeral)]
+async fn bypass_remove(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to modify"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The role which should no longer skip the cooldown"]
+    #[rename = "role"]
+    role_id: RoleId,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    if !form.remove_cooldown_bypass_role(role_id) {
+        say(ctx, "cooldown-bypass-missing").await?;
+        return Ok(());
+    }
+
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say_args(ctx, "cooldown-bypass-removed", &[("role", &role_id.mention())]).await?;
+    Ok(())
+}
+
+/// Makes the cooldown of a form reset at a fixed time each day, week or month
+#[poise::command(slash_command, ephemeral)]
+async fn schedule(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to modify"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "How often the cooldown resets (leave it out to clear)"]
+    period: Option<Period>,
+    #[description = "The time of day the cooldown resets at (e.g. `18:30`; defaults to midnight)"]
+    time: Option<String>,
+    #[description = "The timezone of the reset time (defaults to UTC)"]
+    #[autocomplete = "autocomplete_timezone"]
+    timezone: Option<String>,
+    #[description = "The day of the week weekly cooldowns reset on (defaults to Monday)"]
+    weekday: Option<Day>,
+    #[description = "The day of the month monthly cooldowns reset on (defaults to the 1st)"]
+    #[min = 1]
+    #[max = 28]
+    day: Option<u32>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+
+    let Some(period) = period else {
+        form.set_calendar_cooldown(None);
+        ctx.data.save_form(form_ref.guild_id, &form).await?;
+        say(ctx, "cooldown-schedule-cleared").await?;
+        return Ok(());
+    };
+
+    let calendar_cooldown = CalendarCooldown {
+        period: match period {
+            Period::Daily => ResetPeriod::Daily,
+            Period::Weekly => ResetPeriod::Weekly(weekday.map_or(Weekday::Mon, Weekday::from)),
+            Period::Monthly => ResetPeriod::Monthly(day.unwrap_or(1)),
+        },
+        time: time.map(parse_time).transpose()?.unwrap_or_default(),
+        timezone: timezone.map(parse_timezone).transpose()?.unwrap_or(Tz::UTC),
+    };
+    form.set_calendar_cooldown(Some(calendar_cooldown));
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+
+    let next_reset = FormattedTimestamp::new(calendar_cooldown.next_reset(Utc::now()).into(), Some(FormattedTimestampStyle::LongDateTime));
+    say_args(ctx, "cooldown-schedule-set", &[("time", &next_reset)]).await?;
+    Ok(())
+}
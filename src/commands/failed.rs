@@ -0,0 +1,136 @@
+use poise::CreateReply;
+use poise::serenity_prelude::{CreateEmbed, FormattedTimestamp, FormattedTimestampStyle};
+
+use crate::{ApplicationContext, Context, Error};
+use crate::errors::UserFriendlyError;
+use crate::i18n::{command_language, tr, tr_args};
+use crate::responses::{retry_submission, truncate_answers};
+use crate::state::{EMBED_DESCRIPTION_MAX_LENGTH, FailedSubmission, SubmissionId};
+
+use super::autocomplete::autocomplete_failed;
+use super::{say, say_args, submitter};
+
+async fn get_failed(ctx: ApplicationContext<'_>, id: SubmissionId) -> Result<FailedSubmission, Error> {
+    ctx.data.get_failed_submission(ctx.guild_id().unwrap(), id).await?
+        .ok_or_else(|| UserFriendlyError::new("failed-submission-not-found").into())
+}
+
+/// Manage submissions which could not be posted
+#[poise::command(slash_command, subcommands("list", "show", "replay", "discard"))]
+pub async fn failed(_ctx: Context<'_>) -> serenity::Result<(), Error> {
+    panic!("called root command")
+}
+
+/// Lists submissions waiting to be posted again
+#[poise::command(slash_command, ephemeral)]
+async fn list(ctx: ApplicationContext<'_>) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let language = command_language(ctx).await?;
+    let guild_id = ctx.guild_id().unwrap();
+    let failed = ctx.data.get_failed_submissions(guild_id).await?;
+
+    if failed.is_empty() {
+        ctx.say(tr(language, "no-failed-submissions")).await?;
+        return Ok(());
+    }
+
+    let forms = ctx.data.get_form_ids(guild_id).await?;
+    let deleted_form = tr(language, "deleted-form");
+    let mut description = String::new();
+    for (i, FailedSubmission { submission, attempts, error }) in failed.iter().enumerate() {
+        let title = forms.iter().find(|(id, _)| *id == submission.form_id).map_or(deleted_form.as_str(), |(_, title)| title.as_str());
+        let submitted_at = FormattedTimestamp::new(submission.submitted_at, Some(FormattedTimestampStyle::RelativeTime));
+        let line = tr_args(language, "failed-submission-line", &[
+            ("id", &submission.id),
+            ("form", &title),
+            ("user", &submitter(language, submission)),
+            ("time", &submitted_at),
+            ("attempts", attempts),
+            ("error", error),
+        ]) + "\n";
+        let more = tr_args(language, "list-truncated", &[("count", &(failed.len() - i))]);
+
+        if description.len() + line.len() + more.len() > EMBED_DESCRIPTION_MAX_LENGTH {
+            description += &more;
+            break;
+        }
+
+        description += &line;
+    }
+
+    ctx.send(CreateReply::default().embed(CreateEmbed::new().title(tr(language, "failed-submissions")).description(description))).await?;
+    Ok(())
+}
+
+/// Shows the answers of a submission waiting to be posted again
+#[poise::command(slash_command, ephemeral)]
+async fn show(
+    ctx: ApplicationContext<'_>,
+    #[description = "The submission to consider"]
+    #[autocomplete = "autocomplete_failed"]
+    submission: SubmissionId,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let language = command_language(ctx).await?;
+    let FailedSubmission { submission, attempts, error } = get_failed(ctx, submission).await?;
+    let form = ctx.data.get_form(submission.form_ref()).await?;
+    let deleted_form = tr(language, "deleted-form");
+
+    let description = tr_args(language, "failed-submission-details", &[
+        ("user", &submitter(language, &submission)),
+        ("attempts", &attempts),
+        ("error", &error),
+    ]);
+    let title = form.as_ref().map_or(deleted_form.as_str(), |f| f.title());
+    let answers = submission.answers.iter().enumerate()
+        .map(|(i, answer)| (form.as_ref().and_then(|f| f.fields().get(i)).map_or_else(|| (i + 1).to_string(), |f| f.name().to_owned()), answer.as_str()))
+        .collect::<Vec<_>>();
+    let values = truncate_answers(&answers, title.chars().count() + description.chars().count());
+    let mut embed = CreateEmbed::new()
+        .title(title)
+        .description(description)
+        .timestamp(submission.submitted_at);
+
+    for ((name, _), value) in answers.into_iter().zip(values) {
+        embed = embed.field(name, value, false);
+    }
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Tries to post a submission again right away
+#[poise::command(slash_command, ephemeral)]
+async fn replay(
+    ctx: ApplicationContext<'_>,
+    #[description = "The submission to post"]
+    #[autocomplete = "autocomplete_failed"]
+    submission: SubmissionId,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let failed = get_failed(ctx, submission).await?;
+
+    match retry_submission(ctx.http(), ctx.data, failed).await? {
+        Ok(thread) => say_args(ctx, "failed-submission-replayed", &[("thread", &thread)]).await?,
+        Err(error) => say_args(ctx, "failed-submission-replay-failed", &[("error", &error)]).await?,
+    }
+
+    Ok(())
+}
+
+/// Deletes a submission without posting it
+#[poise::command(slash_command, ephemeral)]
+async fn discard(
+    ctx: ApplicationContext<'_>,
+    #[description = "The submission to delete"]
+    #[autocomplete = "autocomplete_failed"]
+    submission: SubmissionId,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    if !ctx.data.delete_failed_submission(ctx.guild_id().unwrap(), submission).await? {
+        return Err(UserFriendlyError::new("failed-submission-not-found").into());
+    }
+
+    say(ctx, "failed-submission-discarded").await?;
+    Ok(())
+}
@@ -2,16 +2,16 @@ use serenity::all::InputTextStyle;
 
 use crate::{ApplicationContext, Context, Error};
 use crate::errors::UserFriendlyError;
-use crate::state::{AddFieldError, FormField, FormRef};
+use crate::state::{AddFieldError, ConditionEffect, ConditionError, ConditionOperator, FormField, FormRef, PageError, QuizAnswer};
 
 use super::autocomplete::{autocomplete_field, autocomplete_form};
-use super::get_form;
+use super::{get_form, say, say_args};
 
 /// Manages the fields of forms
 #[poise::command(
     slash_command,
     ephemeral,
-    subcommands("add", "remove", "rename", "style", "placeholder", "validation", "inline", "move_field"
+    subcommands("add", "remove", "rename", "style", "placeholder", "validation", "inline", "move_field", "page", "condition", "quiz"
     )
 )]
 pub async fn fields(_ctx: Context<'_>) -> serenity::Result<(), Error> {
@@ -35,6 +35,50 @@ impl From<FieldStyle> for InputTextStyle {
     }
 }
 
+#[derive(poise::ChoiceParameter)]
+enum Operator {
+    #[name = "Equals"]
+    Equals,
+    #[name = "Does not equal"]
+    NotEquals,
+    #[name = "Contains"]
+    Contains,
+}
+
+impl From<Operator> for ConditionOperator {
+    fn from(value: Operator) -> Self {
+        match value {
+            Operator::Equals => Self::Equals,
+            Operator::NotEquals => Self::NotEquals,
+            Operator::Contains => Self::Contains,
+        }
+    }
+}
+
+#[derive(poise::ChoiceParameter)]
+enum Effect {
+    #[name = "Only show the field"]
+    Show,
+    #[name = "Only require the field"]
+    Require,
+}
+
+impl From<Effect> for ConditionEffect {
+    fn from(value: Effect) -> Self {
+        match value {
+            Effect::Show => Self::Show,
+            Effect::Require => Self::Require,
+        }
+    }
+}
+
+fn condition_error(error: ConditionError) -> Error {
+    match error {
+        ConditionError::UnknownField => UserFriendlyError::new("field-not-found").into(),
+        ConditionError::NotOnEarlierPage => UserFriendlyError::new("condition-not-on-earlier-page").into(),
+    }
+}
+
 /// Adds a field to a form
 #[poise::command(slash_command, ephemeral)]
 async fn add(
@@ -51,33 +95,39 @@ async fn add(
     #[max_length = 100]
     placeholder: Option<String>,
     #[description = "The minimum length of responses (always at least 1 if required)"]
-    #[max = 1024]
+    #[max = 4000]
     min_length: Option<u16>,
-    #[description = "The maximum length of responses"]
+    #[description = "The maximum length of responses (at most 1024 for short fields)"]
     #[min = 1]
-    #[max = 1024]
+    #[max = 4000]
     max_length: Option<u16>,
     #[description = "Whether the field is required (defaults to true)"] required: Option<bool>,
     #[description = "Whether to add this field before another existing field; otherwise, it is added to the bottom"]
     #[autocomplete = "autocomplete_field"]
     add_before: Option<usize>,
     #[description = "Whether to inline the field when printing responses (defaults to false)"] inline: Option<bool>,
+    #[description = "The page to add the field to (defaults to the first)"]
+    #[min = 1]
+    #[max = 5]
+    page: Option<u8>,
 ) -> serenity::Result<(), Error> {
     let mut form = get_form(ctx, form_ref).await?;
     let mut field = FormField::new(name, style.into())?;
+    field.set_page(page.map_or(0, |p| p - 1));
     field.min_length = min_length;
     field.max_length = max_length;
     field.required = required.unwrap_or(true);
     field.inline = inline.unwrap_or(false);
     field.set_placeholder(placeholder)?;
+    check_lengths(&field)?;
 
     match form.add_field(field, add_before) {
         Ok(_) => {
             ctx.data.save_form(ctx.guild_id().unwrap(), &form).await?;
-            ctx.say("Field was added").await?
+            say(ctx, "field-added").await?
         }
-        Err(AddFieldError::IllegalAddBefore) => ctx.say("`add_before` is not valid").await?,
-        Err(AddFieldError::TooManyFields) => ctx.say("The maximum amount of fields has been reached").await?,
+        Err(AddFieldError::IllegalAddBefore | AddFieldError::OtherPage) => say(ctx, "invalid-add-before").await?,
+        Err(AddFieldError::TooManyFields) => say(ctx, "too-many-fields").await?,
     };
 
     Ok(())
@@ -97,15 +147,28 @@ async fn remove(
 ) -> serenity::Result<(), Error> {
     let mut form = get_form(ctx, form_ref).await?;
     if form.remove_field(field) {
-        ctx.say("Field was removed").await?;
+        say(ctx, "field-removed").await?;
         ctx.data.save_form(ctx.guild_id().unwrap(), &form).await?;
     } else {
-        ctx.say("Unknown field").await?;
+        say(ctx, "unknown-field").await?;
     }
 
     Ok(())
 }
 
+/// Rejects lengths which the style of the field does not allow.
+fn check_lengths(field: &FormField) -> Result<(), Error> {
+    let max = field.response_max_length();
+    if field.min_length.max(field.max_length).is_some_and(|l| l > max) {
+        return Err(UserFriendlyError::new("field-length-too-long").with_arg("max", max).into());
+    }
+
+    match (field.min_length, field.max_length) {
+        (Some(min), Some(max)) if min > max => Err(UserFriendlyError::new("field-lengths-inverted").into()),
+        _ => Ok(()),
+    }
+}
+
 async fn update_field<F: FnOnce(&mut FormField) -> Result<(), Error>>(
     ctx: ApplicationContext<'_>,
     form_ref: FormRef,
@@ -115,10 +178,11 @@ async fn update_field<F: FnOnce(&mut FormField) -> Result<(), Error>>(
     ctx.defer_ephemeral().await?;
     let mut form = get_form(ctx, form_ref).await?;
     let field = form.fields_mut().get_mut(field)
-        .ok_or_else(|| UserFriendlyError::new("Field could not be found"))?;
+        .ok_or_else(|| UserFriendlyError::new("field-not-found"))?;
     updater(field)?;
+    check_lengths(field)?;
     ctx.data.save_form(form_ref.guild_id, &form).await?;
-    ctx.say("Field updated").await?;
+    say(ctx, "field-updated").await?;
     Ok(())
 }
 
@@ -158,6 +222,9 @@ async fn style(
 ) -> serenity::Result<(), Error> {
     update_field(ctx, form_ref, field, |field| {
         field.style = style.into();
+        if field.quiz().is_some() && field.style != InputTextStyle::Short {
+            return Err(UserFriendlyError::new("quiz-requires-short-field").into());
+        }
         Ok(())
     }).await
 }
@@ -195,11 +262,11 @@ async fn validation(
     #[autocomplete = "autocomplete_field"]
     field: usize,
     #[description = "The new minimum length of responses (always at least 1 if required)"]
-    #[max = 1024]
+    #[max = 4000]
     min_length: Option<u16>,
-    #[description = "The new maximum length of responses"]
+    #[description = "The new maximum length of responses (at most 1024 for short fields)"]
     #[min = 1]
-    #[max = 1024]
+    #[max = 4000]
     max_length: Option<u16>,
     #[description = "Whether the field is required (defaults to true)"] required: Option<bool>,
 ) -> serenity::Result<(), Error> {
@@ -243,7 +310,7 @@ async fn move_field(
     field: usize,
     #[description = "The new position for this field"]
     #[min = 1]
-    #[max = 5]
+    #[max = 25]
     position: usize,
 ) -> serenity::Result<(), Error> {
     ctx.defer_ephemeral().await?;
@@ -251,14 +318,150 @@ async fn move_field(
     match form.move_field(field, position - 1) {
         Ok(true) => {
             ctx.data.save_form(form_ref.guild_id, &form).await?;
-            ctx.say("Field moved").await?;
+            say(ctx, "field-moved").await?;
         }
-        Ok(false) => { ctx.say("Unknown field").await?; }
+        Ok(false) => { say(ctx, "unknown-field").await?; }
         Err(AddFieldError::IllegalAddBefore) => {
-            ctx.say(format!("The form has {0} fields thus position must be between 1 and {0}", form.fields().len())).await?;
+            say_args(ctx, "invalid-position", &[("count", &form.fields().len())]).await?;
         }
+        Err(AddFieldError::OtherPage) => { say(ctx, "position-other-page").await?; }
         Err(e) => { return Err(e.into()); }
     }
 
     Ok(())
 }
+
+/// Moves a field to another page; each page is shown as its own modal
+#[poise::command(slash_command, ephemeral)]
+async fn page(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The field to update"]
+    #[autocomplete = "autocomplete_field"]
+    field: usize,
+    #[description = "The page to show the field on"]
+    #[min = 1]
+    #[max = 5]
+    page: u8,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    match form.set_field_page(field, page - 1) {
+        Ok(true) => {
+            ctx.data.save_form(form_ref.guild_id, &form).await?;
+            say(ctx, "field-updated").await?;
+        }
+        Ok(false) => say(ctx, "field-not-found").await?,
+        Err(PageError::PageFull) => say(ctx, "page-full").await?,
+        Err(PageError::BreaksCondition) => say(ctx, "page-breaks-condition").await?,
+    }
+
+    Ok(())
+}
+
+/// Shows or requires a field only if the answer to a field on an earlier page matches
+#[poise::command(slash_command, ephemeral)]
+async fn condition(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The field to update"]
+    #[autocomplete = "autocomplete_field"]
+    field: usize,
+    #[description = "The field whose answer decides (leave it out to remove the condition)"]
+    #[autocomplete = "autocomplete_field"]
+    depends_on: Option<usize>,
+    #[description = "How the answer is compared (defaults to equals)"] operator: Option<Operator>,
+    #[description = "The answer to compare with; case is ignored"]
+    #[max_length = 100]
+    value: Option<String>,
+    #[description = "Whether the condition decides if the field is shown or required (defaults to shown)"] effect: Option<Effect>,
+) -> serenity::Result<(), Error> {
+    let condition = match (depends_on, value) {
+        (Some(depends_on), Some(value)) => Some((
+            effect.map_or(ConditionEffect::Show, Into::into),
+            depends_on,
+            operator.map_or(ConditionOperator::Equals, Into::into),
+            value,
+        )),
+        (None, _) => None,
+        (Some(_), None) => return Err(UserFriendlyError::new("condition-incomplete").into()),
+    };
+
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    if !form.set_field_condition(field, condition).map_err(condition_error)? {
+        return Err(UserFriendlyError::new("field-not-found").into());
+    }
+
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say(ctx, "field-updated").await
+}
+
+/// Shows a page only if the answer to a field on an earlier page matches
+#[poise::command(slash_command, rename = "page-condition", ephemeral)]
+pub async fn page_condition(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The page to update"]
+    #[min = 2]
+    #[max = 5]
+    page: u8,
+    #[description = "The field whose answer decides (leave it out to remove the condition)"]
+    #[autocomplete = "autocomplete_field"]
+    depends_on: Option<usize>,
+    #[description = "How the answer is compared (defaults to equals)"] operator: Option<Operator>,
+    #[description = "The answer to compare with; case is ignored"]
+    #[max_length = 100]
+    value: Option<String>,
+) -> serenity::Result<(), Error> {
+    let condition = match (depends_on, value) {
+        (Some(depends_on), Some(value)) => Some((depends_on, operator.map_or(ConditionOperator::Equals, Into::into), value)),
+        (None, _) => None,
+        (Some(_), None) => return Err(UserFriendlyError::new("condition-incomplete").into()),
+    };
+
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    form.set_page_condition(page - 1, condition).map_err(condition_error)?;
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say(ctx, "page-condition-updated").await
+}
+
+/// Makes a field a quiz question which earns points if answered correctly
+#[poise::command(slash_command, ephemeral)]
+async fn quiz(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The field to update"]
+    #[autocomplete = "autocomplete_field"]
+    field: usize,
+    #[description = "The correct answers, separated by commas; case is ignored (leave it out to stop scoring the field)"]
+    #[max_length = 1000]
+    answers: Option<String>,
+    #[description = "The points a correct answer is worth (defaults to 1)"]
+    #[min = 1]
+    #[max = 1000]
+    points: Option<u32>,
+) -> serenity::Result<(), Error> {
+    let quiz = answers.map(|answers| QuizAnswer {
+        answers: answers.split(',').map(str::trim).filter(|a| !a.is_empty()).map(str::to_owned).collect(),
+        points: points.unwrap_or(1),
+    });
+
+    update_field(ctx, form_ref, field, |field| {
+        field.set_quiz(quiz).map_err(|_| UserFriendlyError::new("quiz-requires-short-field"))?;
+        Ok(())
+    }).await
+}
@@ -0,0 +1,197 @@
+use poise::{ChoiceParameter, CreateReply};
+use poise::serenity_prelude::{CreateEmbed, GuildChannel, Mentionable};
+
+use crate::{ApplicationContext, Context, Error};
+use crate::errors::UserFriendlyError;
+use crate::i18n::{command_language, tr, tr_args};
+use crate::state::{AddTermError, BlockedTerm, EMBED_DESCRIPTION_MAX_LENGTH, FilterAction, FormRef, MAX_BLOCKED_TERMS, WordFilter};
+
+use super::autocomplete::autocomplete_form;
+use super::{get_form, say, say_args};
+
+#[derive(ChoiceParameter)]
+enum TermKind {
+    #[name = "Whole word"]
+    Word,
+    #[name = "Regular expression"]
+    Pattern,
+}
+
+#[derive(ChoiceParameter)]
+enum Action {
+    #[name = "Reject the submission"]
+    Reject,
+    #[name = "Post it redacted and flagged"]
+    Flag,
+}
+
+impl From<Action> for FilterAction {
+    fn from(value: Action) -> Self {
+        match value {
+            Action::Reject => Self::Reject,
+            Action::Flag => Self::Flag,
+        }
+    }
+}
+
+/// Blocks terms in answers to the forms of the server
+#[poise::command(slash_command, subcommands("add", "remove", "list", "action", "log"))]
+pub async fn filter(_ctx: Context<'_>) -> serenity::Result<(), Error> {
+    panic!("called root command")
+}
+
+/// Changes the word filter of a form, or of the whole server if no form is given.
+async fn update_filter<T, F: FnOnce(&mut WordFilter) -> Result<T, Error>>(
+    ctx: ApplicationContext<'_>,
+    form_ref: Option<FormRef>,
+    updater: F,
+) -> Result<T, Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    match form_ref {
+        Some(form_ref) => {
+            let mut form = get_form(ctx, form_ref).await?;
+            let result = updater(&mut form.word_filter)?;
+            ctx.data.save_form(guild_id, &form).await?;
+            Ok(result)
+        }
+        None => {
+            let mut settings = ctx.data.get_guild_settings(guild_id).await?;
+            let result = updater(&mut settings.word_filter)?;
+            ctx.data.save_guild_settings(guild_id, &settings).await?;
+            Ok(result)
+        }
+    }
+}
+
+/// Blocks a word or regular expression
+#[poise::command(slash_command, ephemeral)]
+async fn add(
+    ctx: ApplicationContext<'_>,
+    #[description = "The word or regular expression to block; case is ignored"]
+    #[max_length = 200]
+    term: String,
+    #[description = "How the term is matched (defaults to whole word)"] kind: Option<TermKind>,
+    #[description = "The form to block it in (leave it out for every form)"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: Option<FormRef>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let term = match kind {
+        Some(TermKind::Pattern) => BlockedTerm::Pattern(term),
+        Some(TermKind::Word) | None => BlockedTerm::Word(term.trim().to_owned()),
+    };
+
+    let added = update_filter(ctx, form_ref, |filter| filter.add_term(term).map_err(|e| match e {
+        AddTermError::TooManyTerms => UserFriendlyError::new("too-many-blocked-terms").with_arg("max", MAX_BLOCKED_TERMS).into(),
+        AddTermError::InvalidPattern(e) => UserFriendlyError::new("invalid-blocked-pattern").with_arg("error", e).into(),
+    })).await?;
+
+    say(ctx, if added { "blocked-term-added" } else { "blocked-term-exists" }).await
+}
+
+/// Unblocks a word or regular expression
+#[poise::command(slash_command, ephemeral)]
+async fn remove(
+    ctx: ApplicationContext<'_>,
+    #[description = "The term to unblock, exactly as it was added"]
+    #[max_length = 200]
+    term: String,
+    #[description = "The form to unblock it in (leave it out for the server's terms)"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: Option<FormRef>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    if !update_filter(ctx, form_ref, |filter| Ok(filter.remove_term(&term) || filter.remove_term(term.trim()))).await? {
+        return Err(UserFriendlyError::new("blocked-term-not-found").into());
+    }
+    say(ctx, "blocked-term-removed").await
+}
+
+/// Lists blocked terms
+#[poise::command(slash_command, ephemeral)]
+async fn list(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to list the terms of (leave it out for the server's terms)"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: Option<FormRef>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let language = command_language(ctx).await?;
+    let (title, filter) = match form_ref {
+        Some(form_ref) => {
+            let form = get_form(ctx, form_ref).await?;
+            (form.title().to_owned(), form.word_filter)
+        }
+        None => (tr(language, "server-blocked-terms"), ctx.data.get_guild_settings(ctx.guild_id().unwrap()).await?.word_filter),
+    };
+
+    if filter.terms().is_empty() {
+        ctx.say(tr(language, "no-blocked-terms")).await?;
+        return Ok(());
+    }
+
+    let action = tr(language, match filter.action {
+        FilterAction::Reject => "filter-action-reject",
+        FilterAction::Flag => "filter-action-flag",
+    });
+    let mut description = tr_args(language, "filter-action-value", &[("action", &action)]) + "\n";
+    let pattern = tr(language, "blocked-term-pattern");
+    for (i, term) in filter.terms().iter().enumerate() {
+        let line = match term {
+            BlockedTerm::Word(word) => format!("- `{word}`\n"),
+            BlockedTerm::Pattern(regex) => format!("- `{regex}` ({pattern})\n"),
+        };
+        let more = tr_args(language, "list-truncated", &[("count", &(filter.terms().len() - i))]);
+
+        if description.len() + line.len() + more.len() > EMBED_DESCRIPTION_MAX_LENGTH {
+            description += &more;
+            break;
+        }
+
+        description += &line;
+    }
+
+    ctx.send(CreateReply::default().embed(CreateEmbed::new().title(title).description(description))).await?;
+    Ok(())
+}
+
+/// Changes what happens to submissions containing blocked terms
+#[poise::command(slash_command, ephemeral)]
+async fn action(
+    ctx: ApplicationContext<'_>,
+    #[description = "What happens to the submission"] action: Action,
+    #[description = "The form to change (leave it out for the server's terms)"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: Option<FormRef>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    update_filter(ctx, form_ref, |filter| {
+        filter.action = action.into();
+        Ok(())
+    }).await?;
+    say(ctx, "filter-action-changed").await
+}
+
+/// Sets the channel submissions containing blocked terms are reported in
+#[poise::command(slash_command, ephemeral)]
+async fn log(
+    ctx: ApplicationContext<'_>,
+    #[description = "The channel to report in (leave it out to stop reporting)"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let guild_id = ctx.guild_id().unwrap();
+    let mut settings = ctx.data.get_guild_settings(guild_id).await?;
+    settings.filter_log_channel = channel.map(|c| c.id);
+    ctx.data.save_guild_settings(guild_id, &settings).await?;
+
+    match settings.filter_log_channel {
+        Some(channel) => say_args(ctx, "filter-log-set", &[("channel", &channel.mention())]).await,
+        None => say(ctx, "filter-log-cleared").await,
+    }
+}
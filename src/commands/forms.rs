@@ -1,13 +1,17 @@
-use poise::{ChoiceParameter, CreateReply};
+use chrono::Utc;
+use poise::CreateReply;
 use poise::serenity_prelude::*;
 
 use crate::{ApplicationContext, Error};
 use crate::errors::UserFriendlyError;
-use crate::responses::create_response;
-use crate::state::{Form, FormField, FormId, FormRef, SerializableMention};
+use crate::i18n::{command_language, tr, tr_args};
+use crate::launchers::sync_form;
+use crate::locales::locale_name;
+use crate::modals::ModalKind;
+use crate::state::{Condition, ConditionEffect, ConditionOperator, FilterAction, Form, FormField, FormId, FormRef, QuizSettings, Quota, SerializableMention, SubmissionId};
 
-use super::{CUSTOM_ID_PREFIX, get_form, parse_cooldown};
-use super::autocomplete::autocomplete_form;
+use super::{get_form, parse_cooldown, parse_locale, say, say_args};
+use super::autocomplete::{autocomplete_form, autocomplete_locale};
 
 /// Creates a new form
 #[poise::command(slash_command, rename = "create", ephemeral)]
@@ -37,7 +41,7 @@ pub async fn create_form(
     form.set_cooldown(cooldown.map(parse_cooldown).transpose()?);
 
     ctx.data.save_form(ctx.guild_id().unwrap(), &form).await?;
-    ctx.say("Form was created").await?;
+    say(ctx, "form-created").await?;
 
     Ok(())
 }
@@ -51,16 +55,19 @@ pub async fn delete_form(
     #[autocomplete = "autocomplete_form"]
     form_id: FormId,
 ) -> serenity::Result<(), Error> {
-    if ctx.data.delete_form(ctx.guild_id().unwrap(), form_id).await? {
-        ctx.say("Form was deleted").await?;
+    ctx.defer_ephemeral().await?;
+    let guild_id = ctx.guild_id().unwrap();
+    if ctx.data.delete_form(guild_id, form_id).await? {
+        sync_form(ctx.http(), ctx.data, FormRef::new(guild_id, form_id), None).await?;
+        say(ctx, "form-deleted").await?;
     } else {
-        ctx.say("Unknown form").await?;
+        say(ctx, "unknown-form").await?;
     }
 
     Ok(())
 }
 
-/// Changes the destination channel of a form
+/// Renames a form
 #[poise::command(slash_command, ephemeral)]
 pub async fn rename(
     ctx: ApplicationContext<'_>,
@@ -76,7 +83,151 @@ pub async fn rename(
     let mut form = get_form(ctx, form_ref).await?;
     form.set_title(title)?;
     ctx.data.save_form(ctx.guild_id().unwrap(), &form).await?;
-    ctx.say("Form was renamed").await?;
+    sync_form(ctx.http(), ctx.data, form_ref, Some(&form)).await?;
+    say(ctx, "form-renamed").await?;
+    Ok(())
+}
+
+/// Pauses or resumes submissions of a form
+#[poise::command(slash_command, ephemeral)]
+pub async fn pause(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to modify"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "Whether the form is paused; its buttons are disabled while paused"]
+    paused: bool,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    form.paused = paused;
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    sync_form(ctx.http(), ctx.data, form_ref, Some(&form)).await?;
+    say(ctx, if paused { "form-paused" } else { "form-resumed" }).await?;
+    Ok(())
+}
+
+/// Fills in the form with the answers of the user's previous submission
+#[poise::command(slash_command, ephemeral)]
+pub async fn prefill(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to modify"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "Whether users see their previous answers when filling in the form again"]
+    enabled: bool,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    form.prefill = enabled;
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say(ctx, if enabled { "prefill-enabled" } else { "prefill-disabled" }).await?;
+    Ok(())
+}
+
+/// Hides who sent submissions from staff in the thread
+#[poise::command(slash_command, ephemeral)]
+pub async fn anonymous(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to modify"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "Whether submissions are posted without the submitter's name"]
+    enabled: bool,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    form.anonymous = enabled;
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say(ctx, if enabled { "anonymous-enabled" } else { "anonymous-disabled" }).await?;
+    Ok(())
+}
+
+/// Lets staff talk to submitters through the bot instead of in the thread
+#[poise::command(slash_command, ephemeral)]
+pub async fn relay(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to modify"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "Whether staff reply with /reply and submitters answer in their DMs"]
+    enabled: bool,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    form.relay = enabled;
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say(ctx, if enabled { "relay-enabled" } else { "relay-disabled" }).await?;
+    Ok(())
+}
+
+/// Sets the points needed to pass a quiz and whether submissions are decided automatically
+#[poise::command(slash_command, ephemeral)]
+pub async fn quiz(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to modify"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The points needed to pass (leave it out to only show scores)"]
+    #[min = 1]
+    threshold: Option<u32>,
+    #[description = "Whether submissions are accepted or denied right away (defaults to false)"]
+    auto_decide: Option<bool>,
+    #[description = "The role given to submitters who pass, if decided automatically"]
+    pass_role: Option<Role>,
+) -> serenity::Result<(), Error> {
+    let auto_decide = auto_decide.unwrap_or(false);
+    if pass_role.is_some() && !auto_decide {
+        return Err(UserFriendlyError::new("quiz-role-requires-auto-decide").into());
+    }
+
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    form.quiz = match threshold {
+        Some(threshold) => Some(QuizSettings { threshold, auto_decide, pass_role: pass_role.map(|r| r.id) }),
+        None if auto_decide => return Err(UserFriendlyError::new("quiz-auto-decide-requires-threshold").into()),
+        None => None,
+    };
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+
+    if !form.fields().iter().any(|f| f.quiz().is_some()) {
+        return say(ctx, "quiz-updated-without-questions").await;
+    }
+    say(ctx, "quiz-updated").await
+}
+
+/// Shows who sent an anonymous submission (administrators only)
+#[poise::command(slash_command, ephemeral)]
+pub async fn reveal(
+    ctx: ApplicationContext<'_>,
+    #[description = "The submission ID shown below an anonymous submission"]
+    submission: SubmissionId,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let is_admin = ctx.interaction.member.as_ref()
+        .and_then(|m| m.permissions)
+        .is_some_and(|p| p.administrator());
+    if !is_admin {
+        return Err(UserFriendlyError::new("reveal-requires-administrator").into());
+    }
+
+    let guild_id = ctx.guild_id().unwrap();
+    let record = ctx.data.get_submission(guild_id, submission).await?
+        .ok_or_else(|| UserFriendlyError::new("submission-not-found"))?;
+
+    // Leave a trail, so looking up anonymous submitters can be audited
+    tracing::info!(%guild_id, %submission, administrator = %ctx.author().id, "Revealed submitter of submission");
+
+    say_args(ctx, "submission-revealed", &[
+        ("user", &record.submission.user_id.mention()),
+        ("name", &record.submission.user_name),
+        ("thread", &record.thread_id.mention()),
+    ]).await?;
     Ok(())
 }
 
@@ -96,7 +247,7 @@ pub async fn description(
     let mut form = get_form(ctx, form_ref).await?;
     form.set_description(description)?;
     ctx.data.save_form(ctx.guild_id().unwrap(), &form).await?;
-    ctx.say("Form description was changed").await?;
+    say(ctx, "description-changed").await?;
     Ok(())
 }
 
@@ -115,7 +266,34 @@ pub async fn cooldown(
     let mut form = get_form(ctx, form_ref).await?;
     form.set_cooldown(cooldown.map(parse_cooldown).transpose()?);
     ctx.data.save_form(ctx.guild_id().unwrap(), &form).await?;
-    ctx.say("Form cooldown was changed").await?;
+    say(ctx, "cooldown-changed").await?;
+    Ok(())
+}
+
+/// Limits how many times a user may submit a form within a period
+#[poise::command(slash_command, ephemeral)]
+pub async fn quota(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to modify"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "How many submissions a user may make within the window (leave it out to clear)"]
+    #[min = 1]
+    count: Option<u32>,
+    #[description = "The period submissions are counted over (e.g. `24h`)"]
+    window: Option<String>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    let quota = match (count, window) {
+        (Some(count), Some(window)) => Some(Quota { count, window: parse_cooldown(window)? }),
+        (None, None) => None,
+        _ => return Err(UserFriendlyError::new("quota-incomplete").into()),
+    };
+    form.set_quota(quota);
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say(ctx, "quota-changed").await?;
     Ok(())
 }
 
@@ -134,7 +312,7 @@ pub async fn mention(
     let mut form = get_form(ctx, form_ref).await?;
     form.mention = mention;
     ctx.data.save_form(ctx.guild_id().unwrap(), &form).await?;
-    ctx.say("Mention of the form was changed").await?;
+    say(ctx, "mention-changed").await?;
     Ok(())
 }
 
@@ -142,7 +320,7 @@ fn validate_destination(ctx: ApplicationContext<'_>, destination: &GuildChannel)
     if destination.permissions_for_user(ctx, ctx.framework.bot_id)?.create_private_threads() {
         Ok(())
     } else {
-        Err(UserFriendlyError::new(format!("I do not have permission to create private threads in {}", destination)).into())
+        Err(UserFriendlyError::new("missing-thread-permission").with_arg("channel", destination).into())
     }
 }
 
@@ -164,70 +342,7 @@ pub async fn destination(
 
     form.destination = destination.id;
     ctx.data.save_form(ctx.guild_id().unwrap(), &form).await?;
-    ctx.say("Form destination was updated").await?;
-    Ok(())
-}
-
-#[derive(ChoiceParameter)]
-enum ButtonColor {
-    Blurple,
-    Grey,
-    Green,
-    Red,
-}
-
-impl From<ButtonColor> for ButtonStyle {
-    fn from(value: ButtonColor) -> Self {
-        match value {
-            ButtonColor::Blurple => ButtonStyle::Primary,
-            ButtonColor::Grey => ButtonStyle::Secondary,
-            ButtonColor::Green => ButtonStyle::Success,
-            ButtonColor::Red => ButtonStyle::Danger,
-        }
-    }
-}
-
-/// Create a button for a form
-#[poise::command(slash_command, ephemeral)]
-pub async fn button(
-    ctx: ApplicationContext<'_>,
-    #[description = "The form to delete"]
-    #[rename = "form"]
-    #[autocomplete = "autocomplete_form"]
-    form_id: FormId,
-    #[description = "Text for the button"]
-    #[max_length = 80]
-    text: String,
-    #[description = "A string to send with the button"] message: Option<String>,
-    #[description = "The color of the button"] color: ButtonColor,
-    #[description = "An emoji for the button"] emoji: Option<String>,
-) -> serenity::Result<(), Error> {
-    ctx.defer_ephemeral().await?;
-
-    let mut button = CreateButton::new(format!("{CUSTOM_ID_PREFIX}{form_id}"))
-        .label(text)
-        .style(color.into());
-
-    if let Some(emoji) = emoji {
-        let Ok(reaction) = ReactionType::try_from(emoji) else {
-            ctx.say("Failed to parse the provided emoji").await?;
-            return Ok(());
-        };
-
-        button = button.emoji(reaction);
-    }
-
-    let mut create_message = CreateMessage::new()
-        .button(button);
-
-    if let Some(message) = message {
-        create_message = create_message.content(message);
-    }
-
-    ctx.channel_id().send_message(ctx, create_message).await?;
-
-    ctx.say("Button created").await?;
-
+    say(ctx, "destination-updated").await?;
     Ok(())
 }
 
@@ -235,7 +350,7 @@ pub async fn button(
 #[poise::command(slash_command, rename = "show", ephemeral)]
 pub async fn show_form(
     ctx: ApplicationContext<'_>,
-    #[description = "The form to delete"]
+    #[description = "The form to show"]
     #[rename = "form"]
     #[autocomplete = "autocomplete_form"]
     form_ref: FormRef,
@@ -243,21 +358,13 @@ pub async fn show_form(
     create: Option<bool>,
 ) -> serenity::Result<(), Error> {
     let form = get_form(ctx, form_ref).await?;
-    let Some(quick_modal) = form.quick_modal() else {
-        ctx.say("A form must have fields to be shown.").await?;
-        return Ok(());
-    };
-
-    let Some(response) = ctx.interaction.quick_modal(ctx.serenity_context(), quick_modal).await? else {
+    let kind = if create.unwrap_or(false) { ModalKind::Test } else { ModalKind::Preview };
+    let Some(modal) = form.modal(kind, &ctx.interaction.locale, &Default::default()) else {
+        say(ctx, "form-has-no-fields").await?;
         return Ok(());
     };
 
-    if let Some(true) = create {
-        create_response(ctx.serenity_context, &form, response).await?;
-    } else {
-        response.interaction.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
-    }
-
+    ctx.interaction.create_response(ctx, CreateInteractionResponse::Modal(modal)).await?;
     Ok(())
 }
 
@@ -271,42 +378,151 @@ pub async fn form_details(
     form_ref: FormRef,
 ) -> serenity::Result<(), Error> {
     ctx.defer_ephemeral().await?;
+    let language = command_language(ctx).await?;
     let form = get_form(ctx, form_ref).await?;
     let mut embed_builder = CreateEmbed::new()
         .title(form.title());
 
-    fn style_list<const N: usize>(elements: [(&str, Option<String>); N]) -> String {
+    fn style_list<const N: usize>(elements: [(String, Option<String>); N]) -> String {
         elements.into_iter().filter_map(|(name, value)| value.map(|v| (name, v)))
             .map(|(name, v)| format!("- **{}**: {}", name, v))
             .collect::<Vec<_>>()
             .join("\n")
     }
 
-    fn field_details(field: &FormField) -> String {
-        style_list([
-            ("Style", match field.style {
-                InputTextStyle::Short => Some("Short".to_owned()),
-                InputTextStyle::Paragraph => Some("Paragraph".to_owned()),
-                _ => None,
-            }),
-            ("Placeholder", field.placeholder().map(str::to_owned)),
-            ("Minimum length", field.min_length.map(|l| l.to_string())),
-            ("Max length", field.max_length.map(|l| l.to_string())),
-            ("Required", Some(field.required.to_string())),
-            ("In-line", Some(field.inline.to_string())),
-        ])
-    }
+    let yes_no = |value: bool| tr(language, if value { "yes" } else { "no" });
+
+    let describe_condition = |condition: &Condition| {
+        let field = form.fields().iter().find(|f| f.id() == condition.field).map_or("?", |f| f.name());
+        let operator = tr(language, match condition.operator {
+            ConditionOperator::Equals => "operator-equals",
+            ConditionOperator::NotEquals => "operator-not-equals",
+            ConditionOperator::Contains => "operator-contains",
+        });
+        tr_args(language, "condition-value", &[("field", &field), ("operator", &operator), ("value", &condition.value)])
+    };
+
+    let field_details = |field: &FormField| style_list([
+        (tr(language, "detail-page"), Some(field.page() + 1).filter(|_| form.page_count() > 1).map(|p| p.to_string())),
+        (tr(language, "detail-condition"), field.condition().map(|(effect, condition)| {
+            let key = match effect {
+                ConditionEffect::Show => "condition-shows",
+                ConditionEffect::Require => "condition-requires",
+            };
+            tr_args(language, key, &[("condition", &describe_condition(condition))])
+        })),
+        (tr(language, "detail-quiz"), field.quiz().map(|q| tr_args(language, "quiz-answer-value", &[
+            ("answers", &q.answers.join(", ")),
+            ("points", &q.points),
+        ]))),
+        (tr(language, "detail-style"), match field.style {
+            InputTextStyle::Short => Some(tr(language, "style-short")),
+            InputTextStyle::Paragraph => Some(tr(language, "style-paragraph")),
+            _ => None,
+        }),
+        (tr(language, "detail-placeholder"), field.placeholder().map(str::to_owned)),
+        (tr(language, "detail-min-length"), field.min_length.map(|l| l.to_string())),
+        (tr(language, "detail-max-length"), field.max_length.map(|l| l.to_string())),
+        (tr(language, "detail-required"), Some(yes_no(field.required))),
+        (tr(language, "detail-inline"), Some(yes_no(field.inline))),
+        (tr(language, "detail-translations"), Some(field.translations().keys().map(String::as_str).collect::<Vec<_>>())
+            .filter(|l| !l.is_empty()).map(|l| l.join(", "))),
+    ]);
 
     embed_builder = form.fields().iter()
         .fold(embed_builder, |acc, f| acc.field(f.name(), field_details(f), true))
         .description(style_list([
-            ("Destination", Some(form.destination.mention().to_string())),
-            ("Description", form.description().map(str::to_owned)),
-            ("Mentions", form.mention.map(|m| m.to_string())),
-            ("Cooldown", form.cooldown().map(|c| humantime::format_duration(c).to_string())),
+            (tr(language, "detail-destination"), Some(form.destination.mention().to_string())),
+            (tr(language, "detail-description"), form.description().map(str::to_owned)),
+            (tr(language, "detail-mentions"), form.mention.map(|m| m.to_string())),
+            (tr(language, "detail-cooldown"), form.cooldown().map(|c| humantime::format_duration(c).to_string())),
+            (tr(language, "detail-cooldown-schedule"), form.calendar_cooldown().map(|c| {
+                let next_reset = FormattedTimestamp::new(c.next_reset(Utc::now()).into(), Some(FormattedTimestampStyle::LongDateTime));
+                tr_args(language, "cooldown-schedule-value", &[("timezone", &c.timezone.name()), ("time", &next_reset)])
+            })),
+            (tr(language, "detail-quota"), form.quota().map(|q| tr_args(language, "quota-value", &[
+                ("count", &q.count),
+                ("window", &humantime::format_duration(q.window)),
+            ]))),
+            (tr(language, "detail-cooldown-bypass"), Some(form.cooldown_bypass_roles()).filter(|r| !r.is_empty())
+                .map(|r| r.iter().map(|r| r.mention().to_string()).collect::<Vec<_>>().join(", "))),
+            (tr(language, "detail-paused"), Some(yes_no(form.paused))),
+            (tr(language, "detail-prefill"), Some(yes_no(form.prefill))),
+            (tr(language, "detail-anonymous"), Some(yes_no(form.anonymous))),
+            (tr(language, "detail-relay"), Some(yes_no(form.relay))),
+            (tr(language, "detail-quiz"), form.quiz.map(|q| {
+                let mut value = tr_args(language, "quiz-threshold-value", &[("threshold", &q.threshold)]);
+                if q.auto_decide {
+                    value += &tr(language, "quiz-auto-decide-value");
+                }
+                if let Some(role) = q.pass_role {
+                    value += &tr_args(language, "quiz-pass-role-value", &[("role", &role.mention())]);
+                }
+                value
+            })),
+            (tr(language, "detail-blocked-terms"), Some(form.word_filter.terms().len()).filter(|n| *n > 0).map(|count| {
+                let action = tr(language, match form.word_filter.action {
+                    FilterAction::Reject => "filter-action-reject",
+                    FilterAction::Flag => "filter-action-flag",
+                });
+                tr_args(language, "blocked-terms-value", &[("count", &count), ("action", &action)])
+            })),
+            (tr(language, "detail-page-conditions"), Some(form.page_conditions()).filter(|c| !c.is_empty()).map(|c| c.iter()
+                .map(|(page, condition)| tr_args(language, "page-condition-value", &[("page", &(page + 1)), ("condition", &describe_condition(condition))]))
+                .collect::<Vec<_>>().join(", "))),
+            (tr(language, "detail-translations"), Some(form.locales()).filter(|l| !l.is_empty()).map(|l| l.join(", "))),
         ]));
 
     ctx.send(CreateReply::default().embed(embed_builder)).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Sets where transcripts of closed submissions are sent
+#[poise::command(slash_command, ephemeral)]
+pub async fn transcripts(
+    ctx: ApplicationContext<'_>,
+    #[description = "The channel to post transcripts in (leave it out to stop posting them)"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+    #[description = "Whether submitters are also sent the transcript"]
+    dm_submitter: bool,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let language = command_language(ctx).await?;
+    let guild_id = ctx.guild_id().unwrap();
+    let mut settings = ctx.data.get_guild_settings(guild_id).await?;
+    settings.transcript_channel = channel.map(|c| c.id);
+    settings.transcript_dm = dm_submitter;
+    ctx.data.save_guild_settings(guild_id, &settings).await?;
+
+    let channel = match settings.transcript_channel {
+        Some(channel) => tr_args(language, "transcript-channel-set", &[("channel", &channel.mention())]),
+        None => tr(language, "transcript-channel-cleared"),
+    };
+    let dm = tr(language, if dm_submitter { "transcript-dm-enabled" } else { "transcript-dm-disabled" });
+    ctx.say(format!("{channel}\n{dm}")).await?;
+    Ok(())
+}
+
+/// Sets the default language of the server
+#[poise::command(slash_command, ephemeral)]
+pub async fn language(
+    ctx: ApplicationContext<'_>,
+    #[description = "The language of posted responses (leave it out to follow the server's Discord settings)"]
+    #[autocomplete = "autocomplete_locale"]
+    locale: Option<String>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let guild_id = ctx.guild_id().unwrap();
+    let mut settings = ctx.data.get_guild_settings(guild_id).await?;
+    settings.language = locale.map(parse_locale).transpose()?;
+    ctx.data.save_guild_settings(guild_id, &settings).await?;
+
+    match &settings.language {
+        Some(language) => say_args(ctx, "language-set", &[("language", &locale_name(language))]).await?,
+        None => say(ctx, "language-cleared").await?,
+    }
+
+    Ok(())
+}
@@ -1,31 +1,78 @@
 #![allow(clippy::too_many_arguments)]
 
+use std::fmt::Display;
 use std::time::Duration;
 
 use poise::serenity_prelude::*;
 
+use buttons::{button, buttons};
 use cooldowns::cooldowns;
-use fields::fields;
+use failed::failed;
+use filter::filter;
+use fields::{fields, page_condition};
 use forms::*;
+use panels::panel;
+use relay::reply;
+use stats::stats;
+use submissions::submissions;
+use translations::translations;
 
 use crate::{ApplicationContext, Context, Error};
 use crate::errors::UserFriendlyError;
-use crate::event_handler::CUSTOM_ID_PREFIX;
-use crate::state::{Form, FormRef, State};
+use crate::i18n::{command_language, localize_commands, tr, tr_args};
+use crate::locales;
+use crate::state::{Form, FormRef, State, Submission};
 
+mod buttons;
 mod cooldowns;
+mod failed;
+mod filter;
 mod forms;
 mod fields;
+mod panels;
+mod relay;
+mod stats;
+mod submissions;
 mod autocomplete;
+mod translations;
 
 async fn get_form(ctx: ApplicationContext<'_>, form_ref: FormRef) -> Result<Form, Error> {
-    ctx.data.get_form(form_ref).await?.ok_or_else(|| UserFriendlyError::new("Form could not be found").into())
+    ctx.data.get_form(form_ref).await?.ok_or_else(|| UserFriendlyError::new("form-not-found").into())
+}
+
+/// Replies with a translated message.
+async fn say(ctx: ApplicationContext<'_>, key: &str) -> Result<(), Error> {
+    say_args(ctx, key, &[]).await
+}
+
+/// Replies with a translated message with arguments.
+async fn say_args(ctx: ApplicationContext<'_>, key: &str, args: &[(&str, &(dyn Display + Sync))]) -> Result<(), Error> {
+    let language = command_language(ctx).await?;
+    ctx.say(tr_args(language, key, args)).await?;
+    Ok(())
+}
+
+/// Who sent a submission, unless it is anonymous.
+fn submitter(language: &str, submission: &Submission) -> String {
+    if submission.anonymous {
+        tr(language, "anonymous-user")
+    } else {
+        submission.user_id.mention().to_string()
+    }
 }
 
 fn parse_cooldown(cooldown: String) -> Result<Duration, Error> {
     match humantime::parse_duration(&cooldown) {
         Ok(cooldown) => Ok(cooldown),
-        Err(e) => Err(UserFriendlyError::new(format!("Cooldown was not formatted correctly: {e}")).into()),
+        Err(e) => Err(UserFriendlyError::new("invalid-cooldown").with_arg("error", e).into()),
+    }
+}
+
+fn parse_locale(locale: String) -> Result<String, Error> {
+    if locales::is_supported(&locale) {
+        Ok(locale)
+    } else {
+        Err(UserFriendlyError::new("invalid-locale").with_arg("locale", locale).into())
     }
 }
 
@@ -35,7 +82,7 @@ fn parse_cooldown(cooldown: String) -> Result<Duration, Error> {
     guild_only,
     ephemeral,
     default_member_permissions = "MANAGE_CHANNELS",
-    subcommands("create_form", "delete_form", "button", "fields", "destination", "rename", "mention", "show_form", "form_details", "description", "cooldown", "cooldowns"
+    subcommands("create_form", "delete_form", "button", "fields", "destination", "rename", "mention", "show_form", "page_condition", "form_details", "description", "cooldown", "quota", "cooldowns", "translations", "language", "transcripts", "buttons", "pause", "prefill", "anonymous", "relay", "reveal", "quiz", "filter", "panel", "failed", "submissions", "stats"
     )
 )]
 pub async fn forms(_ctx: Context<'_>) -> serenity::Result<(), Error> {
@@ -49,5 +96,7 @@ async fn register(ctx: Context<'_>) -> Result<(), Error> {
 }
 
 pub fn get_commands() -> Vec<poise::Command<State, Error>> {
-    vec![register(), forms()]
+    let mut commands = vec![register(), forms(), reply()];
+    localize_commands(&mut commands);
+    commands
 }
@@ -0,0 +1,232 @@
+use poise::ChoiceParameter;
+use poise::serenity_prelude::*;
+
+use crate::{ApplicationContext, Context, Error};
+use crate::errors::UserFriendlyError;
+use crate::launchers::{is_not_found, panel_contents, update_panel};
+use crate::state::{AddEntryError, FormRef, Panel, PanelEntry, PanelId, PanelKind};
+
+use super::autocomplete::{autocomplete_form, autocomplete_panel};
+use super::buttons::{ButtonColor, parse_emoji};
+use super::{get_form, say};
+
+#[derive(ChoiceParameter)]
+enum PanelStyle {
+    Buttons,
+    #[name = "Select menu"]
+    Select,
+}
+
+impl From<PanelStyle> for PanelKind {
+    fn from(value: PanelStyle) -> Self {
+        match value {
+            PanelStyle::Buttons => PanelKind::Buttons,
+            PanelStyle::Select => PanelKind::Select,
+        }
+    }
+}
+
+#[derive(ChoiceParameter)]
+enum PanelReset {
+    Description,
+    Color,
+    Image,
+}
+
+fn parse_color(color: String) -> Result<Colour, Error> {
+    u32::from_str_radix(color.trim_start_matches('#'), 16)
+        .ok()
+        .filter(|c| *c <= 0xFFFFFF)
+        .map(Colour::new)
+        .ok_or_else(|| UserFriendlyError::new("invalid-color").with_arg("color", color).into())
+}
+
+async fn get_panel(ctx: ApplicationContext<'_>, panel_id: PanelId) -> Result<Panel, Error> {
+    ctx.data.get_panel(ctx.guild_id().unwrap(), panel_id).await?
+        .ok_or_else(|| UserFriendlyError::new("panel-not-found").into())
+}
+
+/// Saves a panel and updates its message; forgets the panel if its message has been deleted.
+async fn save_panel(ctx: ApplicationContext<'_>, panel: &Panel, success: &str) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    if update_panel(ctx.http(), ctx.data, guild_id, panel).await? {
+        ctx.data.save_panel(guild_id, panel).await?;
+        say(ctx, success).await
+    } else {
+        ctx.data.delete_panel(guild_id, panel.id()).await?;
+        say(ctx, "panel-message-deleted").await
+    }
+}
+
+/// Manages panels with buttons or a select menu for several forms
+#[poise::command(slash_command, ephemeral, subcommands("create", "add", "remove", "edit", "delete"))]
+pub async fn panel(_ctx: Context<'_>) -> serenity::Result<(), Error> {
+    panic!("called root command")
+}
+
+/// Posts a new panel in this channel
+#[poise::command(slash_command, ephemeral)]
+async fn create(
+    ctx: ApplicationContext<'_>,
+    #[description = "Whether the panel shows buttons or a select menu"] style: PanelStyle,
+    #[description = "The title of the panel"]
+    #[max_length = 256]
+    title: String,
+    #[description = "The text shown in the panel"]
+    #[max_length = 4096]
+    description: Option<String>,
+    #[description = "The color of the panel as a hex code (e.g. `#5865F2`)"] color: Option<String>,
+    #[description = "A link to an image to show in the panel"] image: Option<String>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let guild_id = ctx.guild_id().unwrap();
+
+    let mut panel = Panel::new(ctx.channel_id(), style.into(), title);
+    panel.description = description;
+    panel.color = color.map(parse_color).transpose()?;
+    panel.image = image;
+
+    let (embed, components) = panel_contents(ctx.data, guild_id, &panel).await?;
+    let create_message = CreateMessage::new().embed(embed).components(components);
+    panel.message_id = ctx.channel_id().send_message(ctx, create_message).await?.id;
+    ctx.data.save_panel(guild_id, &panel).await?;
+
+    say(ctx, "panel-created").await?;
+    Ok(())
+}
+
+/// Adds a form to a panel
+#[poise::command(slash_command, ephemeral)]
+async fn add(
+    ctx: ApplicationContext<'_>,
+    #[description = "The panel to modify"]
+    #[rename = "panel"]
+    #[autocomplete = "autocomplete_panel"]
+    panel_id: PanelId,
+    #[description = "The form to add"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "Text for the button or option (defaults to the title of the form)"]
+    #[max_length = 80]
+    label: Option<String>,
+    #[description = "The color of the button"] color: Option<ButtonColor>,
+    #[description = "An emoji for the button or option"] emoji: Option<String>,
+    #[description = "Text shown below the option in select menus"]
+    #[max_length = 100]
+    description: Option<String>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut panel = get_panel(ctx, panel_id).await?;
+    get_form(ctx, form_ref).await?;
+
+    let entry = PanelEntry {
+        form_id: form_ref.form_id,
+        label,
+        style: color.map(ButtonStyle::from).unwrap_or(ButtonStyle::Primary),
+        emoji: emoji.map(parse_emoji).transpose()?,
+        description,
+    };
+
+    match panel.add_entry(entry) {
+        Ok(()) => save_panel(ctx, &panel, "panel-form-added").await?,
+        Err(AddEntryError::AlreadyAdded) => say(ctx, "panel-form-already-added").await?,
+        Err(AddEntryError::TooManyEntries) => say(ctx, "panel-full").await?,
+    }
+
+    Ok(())
+}
+
+/// Removes a form from a panel
+#[poise::command(slash_command, ephemeral)]
+async fn remove(
+    ctx: ApplicationContext<'_>,
+    #[description = "The panel to modify"]
+    #[rename = "panel"]
+    #[autocomplete = "autocomplete_panel"]
+    panel_id: PanelId,
+    #[description = "The form to remove"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut panel = get_panel(ctx, panel_id).await?;
+
+    if panel.remove_entry(form_ref.form_id) {
+        save_panel(ctx, &panel, "panel-form-removed").await?;
+    } else {
+        say(ctx, "panel-form-missing").await?;
+    }
+
+    Ok(())
+}
+
+/// Edits the header of a panel
+#[poise::command(slash_command, ephemeral)]
+async fn edit(
+    ctx: ApplicationContext<'_>,
+    #[description = "The panel to modify"]
+    #[rename = "panel"]
+    #[autocomplete = "autocomplete_panel"]
+    panel_id: PanelId,
+    #[description = "New title of the panel"]
+    #[max_length = 256]
+    title: Option<String>,
+    #[description = "New text shown in the panel"]
+    #[max_length = 4096]
+    description: Option<String>,
+    #[description = "New color of the panel as a hex code (e.g. `#5865F2`)"] color: Option<String>,
+    #[description = "New link to an image to show in the panel"] image: Option<String>,
+    #[description = "Something to remove from the panel"] reset: Option<PanelReset>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut panel = get_panel(ctx, panel_id).await?;
+
+    match reset {
+        Some(PanelReset::Description) => panel.description = None,
+        Some(PanelReset::Color) => panel.color = None,
+        Some(PanelReset::Image) => panel.image = None,
+        None => {}
+    }
+
+    if let Some(title) = title {
+        panel.title = title;
+    }
+
+    if let Some(description) = description {
+        panel.description = Some(description);
+    }
+
+    if let Some(color) = color {
+        panel.color = Some(parse_color(color)?);
+    }
+
+    if let Some(image) = image {
+        panel.image = Some(image);
+    }
+
+    save_panel(ctx, &panel, "panel-updated").await
+}
+
+/// Deletes a panel and its message
+#[poise::command(slash_command, ephemeral)]
+async fn delete(
+    ctx: ApplicationContext<'_>,
+    #[description = "The panel to delete"]
+    #[rename = "panel"]
+    #[autocomplete = "autocomplete_panel"]
+    panel_id: PanelId,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let panel = get_panel(ctx, panel_id).await?;
+
+    if let Err(e) = panel.channel_id.delete_message(ctx, panel.message_id).await {
+        if !is_not_found(&e) {
+            return Err(e.into());
+        }
+    }
+
+    ctx.data.delete_panel(ctx.guild_id().unwrap(), panel_id).await?;
+    say(ctx, "panel-deleted").await
+}
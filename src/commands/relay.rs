@@ -0,0 +1,48 @@
+use poise::serenity_prelude::{CreateAllowedMentions, CreateMessage, Mentionable};
+
+use crate::{ApplicationContext, Error};
+use crate::errors::UserFriendlyError;
+use crate::i18n::{resolve, tr_args};
+use crate::relay::send_to_submitter;
+use crate::responses::guild_locale;
+
+use super::say;
+
+/// Sends a message to the submitter of the submission in this thread
+#[poise::command(slash_command, guild_only, default_member_permissions = "MANAGE_CHANNELS")]
+pub async fn reply(
+    ctx: ApplicationContext<'_>,
+    #[description = "The message to send"]
+    // Leaves room for the rest of the confirmation posted in the thread
+    #[max_length = 1800]
+    message: String,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let record = ctx.data.get_submission_by_thread(ctx.guild_id().unwrap(), ctx.channel_id()).await?
+        .ok_or_else(|| UserFriendlyError::new("relay-not-a-submission"))?;
+
+    if !record.submission.relay {
+        return Err(UserFriendlyError::new("relay-not-enabled").into());
+    }
+
+    if record.withdrawn {
+        return Err(UserFriendlyError::new("submission-already-withdrawn").into());
+    }
+
+    if record.closed {
+        return Err(UserFriendlyError::new("submission-already-closed").into());
+    }
+
+    if !send_to_submitter(ctx.http(), ctx.data, &record, &message).await? {
+        return Err(UserFriendlyError::new("relay-dm-failed").into());
+    }
+
+    // Posted by the bot rather than as the reply, so that errors above are only shown to the sender
+    let language = resolve([Some(guild_locale(ctx.data, &record.submission).await?.as_str())]);
+    let content = tr_args(language, "relay-reply-sent", &[("user", &ctx.author().mention()), ("message", &message)]);
+    if let Err(e) = ctx.channel_id().send_message(ctx, CreateMessage::new().content(content).allowed_mentions(CreateAllowedMentions::new())).await {
+        tracing::warn!(error = ?e, id = %record.submission.id, "Failed to post relayed reply to thread");
+    }
+
+    say(ctx, "relay-reply-done").await
+}
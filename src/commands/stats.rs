@@ -0,0 +1,63 @@
+use poise::CreateReply;
+use poise::serenity_prelude::{CreateEmbed, CreateEmbedFooter, Timestamp};
+
+use crate::{ApplicationContext, Error};
+use crate::i18n::{command_language, tr, tr_args};
+use crate::state::FormRef;
+use crate::stats::FormStats;
+
+use super::autocomplete::autocomplete_form;
+use super::get_form;
+
+fn percentage(part: u64, total: u64) -> String {
+    format!("{:.0}%", part as f64 * 100.0 / total as f64)
+}
+
+/// Shows how a form is used
+#[poise::command(slash_command, ephemeral)]
+pub async fn stats(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let language = command_language(ctx).await?;
+    let form = get_form(ctx, form_ref).await?;
+    let records = ctx.data.get_form_submissions(form_ref).await?;
+    let stats = FormStats::new(&records, ctx.data.get_form_counters(form_ref).await?, Timestamp::now());
+
+    let lines = [
+        (tr(language, "stats-total"), Some(stats.total.to_string())),
+        (tr(language, "stats-recent"), Some(tr_args(language, "stats-recent-value", &[("day", &stats.last_day), ("week", &stats.last_week)]))),
+        (tr(language, "stats-average"), Some(tr_args(language, "stats-average-value", &[
+            ("day", &format!("{:.1}", stats.per_day)),
+            ("week", &format!("{:.1}", stats.per_day * 7.0)),
+        ]))),
+        (tr(language, "stats-unique-submitters"), Some(stats.unique_submitters.to_string())),
+        (tr(language, "stats-quiz-pass-rate"), stats.quiz_passed.map(|(passed, decided)| tr_args(language, "stats-share-value", &[
+            ("percentage", &percentage(passed as u64, decided as u64)),
+            ("count", &passed),
+            ("total", &decided),
+        ]))),
+        (tr(language, "stats-decision-time"), stats.median_decision_time.map(|t| humantime::format_duration(t).to_string())),
+        (tr(language, "stats-cooldown-rejections"), Some(stats.cooldown_rejections.to_string())),
+        (tr(language, "stats-abandoned"), stats.abandoned.map(|(abandoned, opened)| tr_args(language, "stats-share-value", &[
+            ("percentage", &percentage(abandoned, opened)),
+            ("count", &abandoned),
+            ("total", &opened),
+        ]))),
+    ];
+    let description = lines.into_iter()
+        .filter_map(|(name, value)| value.map(|v| format!("- **{name}**: {v}")))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let embed = CreateEmbed::new()
+        .title(tr_args(language, "stats-title", &[("form", &form.title())]))
+        .description(description)
+        .footer(CreateEmbedFooter::new(tr(language, "stats-footer")));
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use poise::CreateReply;
+use poise::serenity_prelude::{CreateEmbed, FormattedTimestamp, FormattedTimestampStyle, Mentionable, User};
+
+use crate::{ApplicationContext, Context, Error};
+use crate::errors::UserFriendlyError;
+use crate::i18n::{command_language, tr, tr_args};
+use crate::responses::{quiz_summary, truncate_answers};
+use crate::state::{EMBED_DESCRIPTION_MAX_LENGTH, FormRef, SubmissionRecord, format_ticket};
+
+use super::autocomplete::autocomplete_form;
+use super::{get_form, submitter};
+
+/// Find posted submissions by their ticket number
+#[poise::command(slash_command, subcommands("lookup", "search"))]
+pub async fn submissions(_ctx: Context<'_>) -> serenity::Result<(), Error> {
+    panic!("called root command")
+}
+
+/// Shows the submission with a ticket number
+#[poise::command(slash_command, ephemeral)]
+async fn lookup(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form the submission was sent to"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The ticket number of the submission"]
+    #[min = 1]
+    number: u64,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let language = command_language(ctx).await?;
+    let form = get_form(ctx, form_ref).await?;
+    let SubmissionRecord { submission, thread_id, history, withdrawn, .. } = ctx.data.get_submission_by_ticket(form_ref, number).await?
+        .ok_or_else(|| UserFriendlyError::new("ticket-not-found").with_arg("ticket", format_ticket(number)))?;
+
+    let mut description = tr_args(language, "submission-details", &[
+        ("user", &submitter(language, &submission)),
+        ("thread", &thread_id.mention()),
+    ]);
+    if !history.is_empty() {
+        description += "\n";
+        description += &tr_args(language, "submission-edited-marker", &[("count", &history.len())]);
+    }
+    if withdrawn {
+        description += "\n";
+        description += &tr(language, "submission-withdrawn-marker");
+    }
+    if let Some(result) = &submission.quiz {
+        description += "\n";
+        description += &quiz_summary(language, result);
+    }
+
+    // The fields may have changed since, in which case the answers are only numbered
+    let same_fields = form.revision() == submission.revision;
+    let title = format!("{} {}", format_ticket(number), form.title());
+    let answers = submission.answers.iter().enumerate()
+        .map(|(i, answer)| (form.fields().get(i).filter(|_| same_fields).map_or_else(|| (i + 1).to_string(), |f| f.name().to_owned()), answer.as_str()))
+        .collect::<Vec<_>>();
+    let values = truncate_answers(&answers, title.chars().count() + description.chars().count());
+    let mut embed = CreateEmbed::new()
+        .title(title)
+        .description(description)
+        .timestamp(submission.submitted_at);
+
+    for ((name, _), value) in answers.into_iter().zip(values) {
+        embed = embed.field(name, value, false);
+    }
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Searches posted submissions
+#[poise::command(slash_command, ephemeral)]
+async fn search(
+    ctx: ApplicationContext<'_>,
+    #[description = "Only include submissions to this form"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: Option<FormRef>,
+    #[description = "Only include submissions by this user"]
+    user: Option<User>,
+    #[description = "Only include submissions with an answer containing this text"]
+    text: Option<String>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let language = command_language(ctx).await?;
+    let guild_id = ctx.guild_id().unwrap();
+    let text = text.map(|t| t.to_lowercase());
+
+    let mut records = ctx.data.get_submissions(guild_id).await?;
+    records.retain(|SubmissionRecord { submission, .. }| {
+        form_ref.is_none_or(|f| f.form_id == submission.form_id)
+            // Anonymous submitters must not be found by searching for them
+            && user.as_ref().is_none_or(|u| !submission.anonymous && u.id == submission.user_id)
+            && text.as_ref().is_none_or(|t| submission.answers.iter().any(|a| a.to_lowercase().contains(t)))
+    });
+
+    if records.is_empty() {
+        ctx.say(tr(language, "no-submissions-found")).await?;
+        return Ok(());
+    }
+
+    let forms = ctx.data.get_form_ids(guild_id).await?;
+    let deleted_form = tr(language, "deleted-form");
+    let mut description = String::new();
+    for (i, SubmissionRecord { submission, thread_id, withdrawn, .. }) in records.iter().rev().enumerate() {
+        let title = forms.iter().find(|(id, _)| *id == submission.form_id).map_or(deleted_form.as_str(), |(_, title)| title.as_str());
+        let submitted_at = FormattedTimestamp::new(submission.submitted_at, Some(FormattedTimestampStyle::RelativeTime));
+        let mut line = tr_args(language, "submission-line", &[
+            ("ticket", &submission.ticket().unwrap_or_else(|| "-".to_owned())),
+            ("form", &title),
+            ("user", &submitter(language, submission)),
+            ("time", &submitted_at),
+            ("thread", &thread_id.mention()),
+        ]);
+        if *withdrawn {
+            line += &format!(" ({})", tr(language, "submission-withdrawn-marker"));
+        }
+        line += "\n";
+        let more = tr_args(language, "list-truncated", &[("count", &(records.len() - i))]);
+
+        if description.len() + line.len() + more.len() > EMBED_DESCRIPTION_MAX_LENGTH {
+            description += &more;
+            break;
+        }
+
+        description += &line;
+    }
+
+    ctx.send(CreateReply::default().embed(CreateEmbed::new().title(tr(language, "submissions-found")).description(description))).await?;
+    Ok(())
+}
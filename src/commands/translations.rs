@@ -0,0 +1,94 @@
+use crate::{ApplicationContext, Context, Error};
+use crate::errors::UserFriendlyError;
+use crate::state::FormRef;
+
+use super::autocomplete::{autocomplete_field, autocomplete_form, autocomplete_locale};
+use super::{get_form, parse_locale, say};
+
+/// Manages translations of forms
+#[poise::command(slash_command, ephemeral, subcommands("set", "field", "remove"))]
+pub async fn translations(_ctx: Context<'_>) -> serenity::Result<(), Error> {
+    panic!("called root command")
+}
+
+/// Translates the title and description of a form
+#[poise::command(slash_command, ephemeral)]
+async fn set(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The locale of the translation"]
+    #[autocomplete = "autocomplete_locale"]
+    locale: String,
+    #[description = "The translated title (leave it out to use the default)"]
+    #[max_length = 45]
+    title: Option<String>,
+    #[description = "The translated description (leave it out to use the default)"]
+    #[max_length = 4096]
+    description: Option<String>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let locale = parse_locale(locale)?;
+    let mut form = get_form(ctx, form_ref).await?;
+    form.set_translation(locale, title, description)?;
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say(ctx, "form-translation-updated").await?;
+    Ok(())
+}
+
+/// Translates the name and placeholder of a field
+#[poise::command(slash_command, ephemeral)]
+async fn field(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The field to translate"]
+    #[autocomplete = "autocomplete_field"]
+    field: usize,
+    #[description = "The locale of the translation"]
+    #[autocomplete = "autocomplete_locale"]
+    locale: String,
+    #[description = "The translated name (leave it out to use the default)"]
+    #[max_length = 45]
+    name: Option<String>,
+    #[description = "The translated placeholder (leave it out to use the default)"]
+    #[max_length = 100]
+    placeholder: Option<String>,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let locale = parse_locale(locale)?;
+    let mut form = get_form(ctx, form_ref).await?;
+    let field = form.fields_mut().get_mut(field)
+        .ok_or_else(|| UserFriendlyError::new("field-not-found"))?;
+    field.set_translation(locale, name, placeholder)?;
+    ctx.data.save_form(form_ref.guild_id, &form).await?;
+    say(ctx, "field-translation-updated").await?;
+    Ok(())
+}
+
+/// Removes all translations of a form and its fields for a locale
+#[poise::command(slash_command, ephemeral)]
+async fn remove(
+    ctx: ApplicationContext<'_>,
+    #[description = "The form to consider"]
+    #[rename = "form"]
+    #[autocomplete = "autocomplete_form"]
+    form_ref: FormRef,
+    #[description = "The locale to remove"]
+    #[autocomplete = "autocomplete_locale"]
+    locale: String,
+) -> serenity::Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+    let mut form = get_form(ctx, form_ref).await?;
+    if form.remove_translation(&locale) {
+        ctx.data.save_form(form_ref.guild_id, &form).await?;
+        say(ctx, "translations-removed").await?;
+    } else {
+        say(ctx, "no-translations").await?;
+    }
+    Ok(())
+}
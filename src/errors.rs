@@ -1,19 +1,36 @@
 use std::fmt::{Display, Formatter};
 
 use crate::FrameworkError;
+use crate::i18n::{command_language, FALLBACK_LANGUAGE, tr_args};
 
+/// An error which is shown to the user; the message is a key in the translation catalog.
 #[derive(Debug)]
-pub struct UserFriendlyError(String);
+pub struct UserFriendlyError {
+    key: &'static str,
+    args: Vec<(&'static str, String)>,
+}
 
 impl UserFriendlyError {
-    pub fn new<T: Into<String>>(message: T) -> Self {
-        UserFriendlyError(message.into())
+    pub fn new(key: &'static str) -> Self {
+        UserFriendlyError { key, args: vec![] }
+    }
+
+    pub fn with_arg<T: Display>(mut self, name: &'static str, value: T) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    pub fn translate(&self, language: &str) -> String {
+        let args = self.args.iter()
+            .map(|(name, value)| (*name, value as &(dyn Display + Sync)))
+            .collect::<Vec<_>>();
+        tr_args(language, self.key, &args)
     }
 }
 
 impl Display for UserFriendlyError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", &self.0)
+        write!(f, "{}", self.translate(FALLBACK_LANGUAGE))
     }
 }
 
@@ -23,17 +40,28 @@ pub async fn on_error(error: FrameworkError<'_>) {
     match error {
         FrameworkError::Command { ctx, error , .. } => {
             if let Some(error) = error.downcast_ref::<UserFriendlyError>() {
-                if let Err(e) = ctx.say(error.to_string()).await {
+                ctx.data().metrics.validation_failures.with_label_values(&["invalid_input"]).inc();
+
+                let language = match ctx {
+                    poise::Context::Application(ctx) => command_language(ctx).await.unwrap_or(FALLBACK_LANGUAGE),
+                    poise::Context::Prefix(_) => FALLBACK_LANGUAGE,
+                };
+
+                if let Err(e) = ctx.say(error.translate(language)).await {
                     tracing::error!(error = ?e, "Error while handling user-friendly error");
                 }
+            } else {
+                ctx.data().metrics.command_errors.with_label_values(&[&ctx.command().qualified_name]).inc();
             }
 
             tracing::error!(?error, "Error occurred handling command")
         }
+        // The user has already been told that the bot is restarting
+        FrameworkError::CommandCheckFailed { error: None, ctx, .. } if ctx.data().shutdown.is_shutting_down() => {}
         _ => {
             if let Err(e) = poise::builtins::on_error(error).await {
                 tracing::error!("Error while handling error: {}", e);
             }
         }
     }
-}
\ No newline at end of file
+}
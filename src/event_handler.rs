@@ -1,56 +1,312 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use poise::serenity_prelude::*;
 
-use crate::{Error, FrameworkContext};
+use crate::{Error, FrameworkContext, filter, relay, submissions, transcripts};
+use crate::filter::{Incident, IncidentKind};
+use crate::i18n::{FALLBACK_LANGUAGE, tr, tr_args, user_language};
+use crate::modals::{indexed_answers, ModalId, ModalKind};
 use crate::responses::create_response;
-use crate::state::FormRef;
+use crate::state::{EMBED_DESCRIPTION_MAX_LENGTH, FilterAction, Form, FormCounter, FormRef, Rejection};
 
 pub const CUSTOM_ID_PREFIX: &str = "show_form:";
+pub const SELECT_CUSTOM_ID: &str = "show_form_select";
+/// Prefixes the [`ModalId`] of the next page of a form, as modals cannot be answered with another modal.
+const CONTINUE_CUSTOM_ID_PREFIX: &str = "form_continue:";
 
-async fn reply<T: Into<String>>(ctx: &Context, interaction: &ComponentInteraction, message: T) -> Result<(), Error> {
-    interaction.create_response(
-        ctx,
-        CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().ephemeral(true).content(message))
-    ).await?;
-    Ok(())
+/// Finds the form requested by a button or the select menu of a panel.
+fn requested_form(data: &ComponentInteractionData) -> Option<&str> {
+    match &data.kind {
+        ComponentInteractionDataKind::Button => data.custom_id.strip_prefix(CUSTOM_ID_PREFIX),
+        ComponentInteractionDataKind::StringSelect { values } if data.custom_id == SELECT_CUSTOM_ID => {
+            values.first().map(String::as_str)
+        }
+        _ => None,
+    }
 }
 
-pub async fn event_handler(ctx: &Context, event: &FullEvent, framework: FrameworkContext<'_>) -> Result<(), Error> {
-    if let FullEvent::InteractionCreate { interaction: Interaction::Component(interaction @ ComponentInteraction { guild_id: Some(guild_id), .. }) } = event {
-        let custom_id = &interaction.data.custom_id;
-        if !custom_id.starts_with(CUSTOM_ID_PREFIX) {
-            return Ok(());
+fn rejection_message(language: &str, form: &Form, rejection: Rejection) -> Result<String, Error> {
+    Ok(match rejection {
+        Rejection::Cooldown(wait) => {
+            let wait = Duration::from_secs(wait.as_millis().div_ceil(1000) as u64);
+            tr_args(language, "cooldown-active", &[("duration", &humantime::format_duration(wait))])
+        }
+        Rejection::Quota(wait) => {
+            let frees_up = FormattedTimestamp::new(
+                Timestamp::from_unix_timestamp((SystemTime::now() + wait).duration_since(UNIX_EPOCH)?.as_secs() as i64 + 1)?,
+                Some(FormattedTimestampStyle::RelativeTime),
+            );
+            let count = form.quota().map_or(0, |q| q.count);
+            tr_args(language, "quota-reached", &[("count", &count), ("time", &frees_up)])
         }
-        let form_ref = FormRef::new(*guild_id, custom_id[CUSTOM_ID_PREFIX.len()..].parse()?);
+    })
+}
+
+pub fn ephemeral<T: Into<String>>(message: T) -> CreateInteractionResponse {
+    CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().ephemeral(true).content(message))
+}
 
-        if let Some(cooldown) = framework.user_data.cooldown(form_ref, interaction.user.id).await? {
-            reply(ctx, interaction, format!("You have submitted this form recently; please wait {} before trying again", humantime::format_duration(cooldown))).await?;
+/// Shows the modal of a form requested through a button or panel.
+async fn show_form(ctx: &Context, interaction: &ComponentInteraction, guild_id: GuildId, form_id: &str, framework: FrameworkContext<'_>) -> Result<(), Error> {
+    let form_ref = FormRef::new(guild_id, form_id.parse()?);
+    let language = user_language(framework.user_data, Some(guild_id), &interaction.locale, interaction.guild_locale.as_deref()).await?;
+
+    if framework.user_data.shutdown.is_shutting_down() {
+        interaction.create_response(ctx, ephemeral(tr(language, "shutting-down"))).await?;
+        return Ok(());
+    }
+
+    let Some(form) = framework.user_data.get_form(form_ref).await? else {
+        framework.user_data.metrics.validation_failures.with_label_values(&["form_missing"]).inc();
+        interaction.create_response(ctx, ephemeral(tr(language, "form-no-longer-exists"))).await?;
+        return Ok(());
+    };
+
+    let roles = interaction.member.as_ref().map(|m| m.roles.as_slice()).unwrap_or_default();
+    if !form.bypasses_cooldown(roles) {
+        if let Some(rejection) = framework.user_data.check_submission(guild_id, &form, interaction.user.id).await? {
+            framework.user_data.metrics.cooldown_rejections.inc();
+            framework.user_data.count(form_ref, FormCounter::CooldownRejected).await?;
+            interaction.create_response(ctx, ephemeral(rejection_message(language, &form, rejection)?)).await?;
             return Ok(());
         }
+    }
 
-        let Some(form) = framework.user_data.get_form(form_ref).await? else {
-            reply(ctx, interaction, "This form no longer exists").await?;
-            return Ok(());
+    if form.paused {
+        interaction.create_response(ctx, ephemeral(tr(language, "form-is-paused"))).await?;
+        return Ok(());
+    }
+
+    if !form.destination.to_channel(ctx).await?.guild().expect("not a guild channel")
+        .permissions_for_user(ctx, framework.bot_id)?.create_private_threads() {
+        framework.user_data.metrics.validation_failures.with_label_values(&["missing_permissions"]).inc();
+        interaction.create_response(ctx, ephemeral(tr(language, "form-cannot-create-threads"))).await?;
+        return Ok(());
+    }
+
+    let answers = if form.prefill {
+        framework.user_data.get_last_answers(form_ref, interaction.user.id).await?
+    } else {
+        Default::default()
+    };
+
+    let Some(modal) = form.modal(ModalKind::Submit, &interaction.locale, &answers) else {
+        framework.user_data.metrics.validation_failures.with_label_values(&["no_fields"]).inc();
+        interaction.create_response(ctx, ephemeral(tr(language, "form-no-fields"))).await?;
+        return Ok(());
+    };
+
+    framework.user_data.metrics.modals_opened.inc();
+    framework.user_data.count(form_ref, FormCounter::Opened).await?;
+    interaction.create_response(ctx, CreateInteractionResponse::Modal(modal)).await?;
+    Ok(())
+}
+
+/// Shows the modal of the next page of a form through the button posted after answering a page.
+async fn continue_form(ctx: &Context, interaction: &ComponentInteraction, guild_id: GuildId, modal_id: ModalId, framework: FrameworkContext<'_>) -> Result<(), Error> {
+    let form_ref = FormRef::new(guild_id, modal_id.form_id);
+    let language = user_language(framework.user_data, Some(guild_id), &interaction.locale, interaction.guild_locale.as_deref()).await?;
+
+    if framework.user_data.shutdown.is_shutting_down() {
+        interaction.create_response(ctx, ephemeral(tr(language, "shutting-down"))).await?;
+        return Ok(());
+    }
+
+    let Some(form) = framework.user_data.get_form(form_ref).await? else {
+        framework.user_data.metrics.validation_failures.with_label_values(&["form_missing"]).inc();
+        interaction.create_response(ctx, ephemeral(tr(language, "form-no-longer-exists"))).await?;
+        return Ok(());
+    };
+
+    if form.revision() != modal_id.revision {
+        framework.user_data.metrics.validation_failures.with_label_values(&["form_changed"]).inc();
+        interaction.create_response(ctx, ephemeral(tr(language, "form-changed-restart"))).await?;
+        return Ok(());
+    }
+
+    if modal_id.kind == ModalKind::Submit && form.paused {
+        interaction.create_response(ctx, ephemeral(tr(language, "form-is-paused"))).await?;
+        return Ok(());
+    }
+
+    let draft = framework.user_data.get_draft(form_ref, interaction.user.id).await?;
+    if draft.is_empty() {
+        interaction.create_response(ctx, ephemeral(tr(language, "form-draft-expired"))).await?;
+        return Ok(());
+    }
+
+    let prefill = if form.prefill {
+        framework.user_data.get_last_answers(form_ref, interaction.user.id).await?
+    } else {
+        Default::default()
+    };
+
+    let Some(modal) = form.page_modal(modal_id.kind, modal_id.page, &interaction.locale, &prefill, &draft) else {
+        interaction.create_response(ctx, ephemeral(tr(language, "form-changed-restart"))).await?;
+        return Ok(());
+    };
+
+    interaction.create_response(ctx, CreateInteractionResponse::Modal(modal)).await?;
+    Ok(())
+}
+
+/// Posts the answers of a submitted form modal.
+async fn submit_form(ctx: &Context, interaction: &ModalInteraction, guild_id: GuildId, modal_id: ModalId, framework: FrameworkContext<'_>) -> Result<(), Error> {
+    let form_ref = FormRef::new(guild_id, modal_id.form_id);
+    let language = user_language(framework.user_data, Some(guild_id), &interaction.locale, interaction.guild_locale.as_deref()).await?;
+    let member = interaction.member.as_ref().expect("can only be run in guild");
+
+    let kind = match modal_id.kind {
+        ModalKind::Test if !member.permissions.is_some_and(|p| p.manage_channels()) => ModalKind::Preview,
+        kind => kind,
+    };
+    let _in_flight = framework.user_data.shutdown.track();
+
+    let Some(form) = framework.user_data.get_form(form_ref).await? else {
+        framework.user_data.metrics.validation_failures.with_label_values(&["form_missing"]).inc();
+        interaction.create_response(ctx, ephemeral(tr(language, "form-no-longer-exists"))).await?;
+        return Ok(());
+    };
+
+    let answers = indexed_answers(&interaction.data);
+
+    // The answers can no longer be matched up with the fields, so hand them back to the user
+    if form.revision() != modal_id.revision {
+        framework.user_data.metrics.validation_failures.with_label_values(&["form_changed"]).inc();
+        let answers = answers.into_iter().map(|(_, answer)| answer).collect::<Vec<_>>().join("\n\n");
+        let message = CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .content(tr(language, "form-changed"));
+        // Long paragraphs may not fit in an embed, but the user should not lose what they wrote
+        let message = if answers.chars().count() > EMBED_DESCRIPTION_MAX_LENGTH {
+            message.add_file(CreateAttachment::bytes(answers, "answers.txt"))
+        } else {
+            message.embed(CreateEmbed::new().description(answers))
         };
+        interaction.create_response(ctx, CreateInteractionResponse::Message(message)).await?;
+        return Ok(());
+    }
 
-        if !form.destination.to_channel(ctx).await?.guild().expect("not a guild channel")
-            .permissions_for_user(ctx, framework.bot_id)?.create_private_threads() {
-            reply(ctx, interaction, "This form is not correctly configured (cannot create threads)").await?;
-            return Ok(());
+    // Answers to earlier pages are kept until the last page has been answered
+    let first_page = form.next_page(None, &Default::default()) == Some(modal_id.page);
+    let mut draft = if first_page {
+        Default::default()
+    } else {
+        framework.user_data.get_draft(form_ref, interaction.user.id).await?
+    };
+    // The draft expires, and is also cleared when the form is started again in another tab
+    if !first_page && (draft.is_empty() || !form.answered_before(modal_id.page, &draft)) {
+        interaction.create_response(ctx, ephemeral(tr(language, "form-draft-expired"))).await?;
+        return Ok(());
+    }
+    for (i, answer) in answers {
+        if let Some(field) = form.fields().get(i).filter(|f| f.page() == modal_id.page) {
+            draft.insert(field.id(), answer);
         }
+    }
 
-        let Some(quick_modal) = form.quick_modal() else {
-            reply(ctx, interaction, "This form is not correctly configured (no fields on form)").await?;
-            return Ok(());
-        };
+    if let Some(page) = form.next_page(Some(modal_id.page), &draft) {
+        framework.user_data.save_draft(form_ref, interaction.user.id, &draft).await?;
+        let next = ModalId { kind, page, ..modal_id };
+        let button = CreateButton::new(format!("{CONTINUE_CUSTOM_ID_PREFIX}{next}")).label(tr(language, "continue-form"));
+        interaction.create_response(ctx, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .content(tr_args(language, "form-page-answered", &[("page", &(modal_id.page + 1))]))
+            .components(vec![CreateActionRow::Buttons(vec![button])])
+        )).await?;
+        return Ok(());
+    }
+
+    if !first_page {
+        framework.user_data.delete_draft(form_ref, interaction.user.id).await?;
+    }
+
+    if kind == ModalKind::Preview {
+        interaction.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+        return Ok(());
+    }
+
+    let mut answers = form.ordered_answers(&draft);
+
+    if kind == ModalKind::Submit && form.paused {
+        interaction.create_response(ctx, ephemeral(tr(language, "form-is-paused"))).await?;
+        return Ok(());
+    }
+
+    // Checked before reserving, so rejected submissions do not count towards the cooldown
+    let settings = framework.user_data.get_guild_settings(guild_id).await?;
+    let mut flagged = false;
+    if let Some(found) = filter::check_answers(&[&settings.word_filter, &form.word_filter], &answers) {
+        let guild_locale = settings.language.as_deref().or(interaction.guild_locale.as_deref()).unwrap_or(FALLBACK_LANGUAGE);
+        let incident = Incident { kind: IncidentKind::Submission, user: &interaction.user, guild_locale, found: &found };
+        filter::report_incident(&ctx.http, framework.user_data, &settings, &form, incident).await?;
 
-        let Some(response) = interaction.quick_modal(ctx, quick_modal).await? else {
+        if found.action == FilterAction::Reject {
+            interaction.create_response(ctx, ephemeral(tr(language, "submission-blocked"))).await?;
             return Ok(());
-        };
+        }
+        answers = found.redacted;
+        flagged = true;
+    }
+
+    // Another modal of the same form may have been submitted while this one was open
+    let reservation = if kind == ModalKind::Test || form.bypasses_cooldown(&member.roles) {
+        None
+    } else {
+        match framework.user_data.reserve_submission(guild_id, &form, interaction.user.id).await? {
+            Ok(reservation) => Some(reservation),
+            Err(rejection) => {
+                framework.user_data.metrics.cooldown_rejections.inc();
+                framework.user_data.count(form_ref, FormCounter::CooldownRejected).await?;
+                let message = tr_args(language, "submission-duplicate", &[("reason", &rejection_message(language, &form, rejection)?)]);
+                interaction.create_response(ctx, ephemeral(message)).await?;
+                return Ok(());
+            }
+        }
+    };
 
-        create_response(ctx, &form, response).await?;
+    if kind == ModalKind::Submit {
+        framework.user_data.count(form_ref, FormCounter::Submitted).await?;
+    }
 
-        framework.user_data.trigger_cooldown(*guild_id, &form, interaction.user.id).await?;
+    let answers_by_field = form.answers_by_field(&answers);
+    if let Err(e) = create_response(ctx, framework.user_data, &form, interaction, answers, flagged).await {
+        if let Some(reservation) = reservation {
+            framework.user_data.release_submission(reservation).await?;
+        }
+        return Err(e);
+    }
+
+    if kind == ModalKind::Submit && form.prefill {
+        framework.user_data.save_last_answers(form_ref, interaction.user.id, &answers_by_field).await?;
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+pub async fn event_handler(ctx: &Context, event: &FullEvent, framework: FrameworkContext<'_>) -> Result<(), Error> {
+    match event {
+        FullEvent::InteractionCreate { interaction: Interaction::Component(interaction @ ComponentInteraction { guild_id: Some(guild_id), .. }) } => {
+            if let Some(form_id) = requested_form(&interaction.data) {
+                show_form(ctx, interaction, *guild_id, form_id, framework).await?;
+            } else if let Some(modal_id) = interaction.data.custom_id.strip_prefix(CONTINUE_CUSTOM_ID_PREFIX).and_then(|id| id.parse().ok()) {
+                continue_form(ctx, interaction, *guild_id, modal_id, framework).await?;
+            } else if !submissions::handle_button(ctx, interaction, *guild_id, framework).await? {
+                transcripts::handle_close(ctx, interaction, *guild_id, framework).await?;
+            }
+        }
+        FullEvent::InteractionCreate { interaction: Interaction::Modal(interaction @ ModalInteraction { guild_id: Some(guild_id), .. }) } => {
+            if let Ok(modal_id) = interaction.data.custom_id.parse() {
+                submit_form(ctx, interaction, *guild_id, modal_id, framework).await?;
+            } else {
+                submissions::handle_edit(ctx, interaction, *guild_id, framework).await?;
+            }
+        }
+        FullEvent::Message { new_message } if new_message.guild_id.is_none() && !new_message.author.bot => {
+            relay::forward_direct_message(ctx, new_message, framework).await?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
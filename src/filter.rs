@@ -0,0 +1,147 @@
+use poise::serenity_prelude::*;
+
+use crate::Error;
+use crate::i18n::{resolve, tr, tr_args};
+use crate::state::{FilterAction, Form, GuildSettings, State, WordFilter};
+
+/// What blocked terms replace in the answers of flagged submissions.
+const REDACTION: char = '█';
+
+/// What contained the blocked terms of an incident.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum IncidentKind {
+    Submission,
+    /// A direct message relayed to the thread of a submission.
+    RelayedMessage,
+}
+
+/// Blocked terms found in something a user sent.
+pub struct Incident<'a> {
+    pub kind: IncidentKind,
+    pub user: &'a User,
+    /// The locale the incident is reported in.
+    pub guild_locale: &'a str,
+    pub found: &'a FilterMatch,
+}
+
+/// The blocked terms found in the answers of a submission.
+pub struct FilterMatch {
+    /// The strictest action of the filters which found a term.
+    pub action: FilterAction,
+    pub terms: Vec<String>,
+    /// The answers with every blocked term blacked out.
+    pub redacted: Vec<String>,
+}
+
+/// Checks answers against the blocked terms of several filters.
+/// Returns `None` if no term was found.
+pub fn check_answers(filters: &[&WordFilter], answers: &[String]) -> Option<FilterMatch> {
+    let mut action = None;
+    let mut terms = vec![];
+    let mut redacted = answers.to_vec();
+    for filter in filters {
+        for term in filter.terms() {
+            let regex = match term.regex() {
+                Ok(regex) => regex,
+                Err(e) => {
+                    // Terms are checked when they are added, so this only happens if the regex crate changes its mind
+                    tracing::warn!(error = ?e, term = term.text(), "Skipping invalid blocked term");
+                    continue;
+                }
+            };
+
+            let mut matched = false;
+            for answer in redacted.iter_mut().filter(|a| regex.is_match(a)) {
+                matched = true;
+                *answer = regex.replace_all(answer, |c: &regex::Captures| REDACTION.to_string().repeat(c[0].chars().count())).into_owned();
+            }
+
+            if matched {
+                terms.push(term.text().to_owned());
+                if action != Some(FilterAction::Reject) {
+                    action = Some(filter.action);
+                }
+            }
+        }
+    }
+
+    action.map(|action| FilterMatch { action, terms, redacted })
+}
+
+/// Logs a submission or relayed message which contained blocked terms, and reports it to the filter
+/// log channel of the guild if it has one. Only one incident per user is reported there in a while,
+/// so rejected submissions cannot be used to flood the channel.
+pub async fn report_incident(http: &Http, state: &State, settings: &GuildSettings, form: &Form, incident: Incident<'_>) -> Result<(), Error> {
+    let Incident { kind, user, guild_locale, found } = incident;
+    tracing::warn!(form = %form.id(), user = %user.id, ?kind, action = ?found.action, terms = ?found.terms, "Blocked terms found");
+    if kind == IncidentKind::Submission {
+        let action = match found.action {
+            FilterAction::Reject => "rejected",
+            FilterAction::Flag => "flagged",
+        };
+        state.metrics.filtered_submissions.with_label_values(&[action]).inc();
+    }
+
+    let Some(channel) = settings.filter_log_channel else {
+        return Ok(());
+    };
+    if !state.claim_filter_report(channel, user.id).await? {
+        return Ok(());
+    }
+
+    let language = resolve([Some(guild_locale)]);
+    // Anonymous submitters stay hidden; administrators can still find them in the bot's logs
+    let submitter = if form.anonymous { tr(language, "anonymous-user") } else { user.mention().to_string() };
+    let action = tr(language, match found.action {
+        FilterAction::Reject => "filter-action-rejected",
+        FilterAction::Flag => "filter-action-flagged",
+    });
+    let embed = CreateEmbed::new()
+        .title(tr(language, "filter-incident-title"))
+        .description(tr_args(language, match kind {
+            IncidentKind::Submission => "filter-incident",
+            IncidentKind::RelayedMessage => "filter-incident-relayed",
+        }, &[
+            ("form", &form.localized_title(guild_locale)),
+            ("user", &submitter),
+            ("action", &action),
+        ]))
+        .field(tr(language, "filter-incident-terms"), found.terms.iter().map(|t| format!("`{t}`")).collect::<Vec<_>>().join(", "), false)
+        .timestamp(Timestamp::now());
+
+    if let Err(e) = channel.send_message(http, CreateMessage::new().embed(embed)).await {
+        tracing::warn!(error = ?e, %channel, "Failed to report blocked terms to the filter log channel");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{BlockedTerm, FilterAction, WordFilter};
+
+    use super::check_answers;
+
+    #[test]
+    fn blocked_terms_are_redacted() {
+        let mut guild = WordFilter::default();
+        guild.action = FilterAction::Flag;
+        guild.add_term(BlockedTerm::Word("bad".to_owned())).unwrap();
+        guild.add_term(BlockedTerm::Word("f*".to_owned())).unwrap();
+        let mut form = WordFilter::default();
+        form.add_term(BlockedTerm::Pattern("wo+rse".to_owned())).unwrap();
+        assert!(form.add_term(BlockedTerm::Pattern("(".to_owned())).is_err());
+
+        let answers = ["Not badly".to_owned(), "This is BAD, f*!".to_owned()];
+        let found = check_answers(&[&guild, &form], &answers).unwrap();
+        assert_eq!(found.action, FilterAction::Flag);
+        assert_eq!(found.terms, vec!["bad", "f*"]);
+        assert_eq!(found.redacted, vec!["Not badly", "This is ███, ██!"]);
+
+        let answers = ["even wooorse".to_owned()];
+        let found = check_answers(&[&guild, &form], &answers).unwrap();
+        assert_eq!(found.action, FilterAction::Reject);
+        assert_eq!(found.redacted, vec!["even ███████"]);
+
+        assert!(check_answers(&[&guild, &form], &["fine".to_owned()]).is_none());
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::LazyLock;
+
+use poise::serenity_prelude::GuildId;
+use serde::Deserialize;
+
+use crate::{ApplicationContext, Error, locales};
+use crate::state::{GuildSettings, State};
+
+/// The language used when nothing better is available; its catalog must contain every message.
+pub const FALLBACK_LANGUAGE: &str = "en-US";
+
+#[derive(Default, Deserialize)]
+struct CommandLocalization {
+    name: Option<String>,
+    description: Option<String>,
+}
+
+/// Translations for a single language.
+///
+/// Commands are keyed by their qualified name (e.g. `forms fields add`) while parameters and
+/// choices are keyed by their English name or description, as these are shared between commands.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Catalog {
+    messages: HashMap<String, String>,
+    commands: HashMap<String, CommandLocalization>,
+    parameter_names: HashMap<String, String>,
+    parameter_descriptions: HashMap<String, String>,
+    choices: HashMap<String, String>,
+}
+
+static CATALOGS: LazyLock<HashMap<&'static str, Catalog>> = LazyLock::new(|| {
+    [
+        ("en-US", include_str!("en-US.json")),
+        ("da", include_str!("da.json")),
+    ].into_iter()
+        .map(|(language, json)| (language, serde_json::from_str(json).expect("invalid translation catalog")))
+        .collect()
+});
+
+/// Finds the language of the catalog best matching a Discord locale, if any.
+pub fn language(locale: &str) -> Option<&'static str> {
+    locales::candidates(locale, CATALOGS.keys().copied()).into_iter().next()
+}
+
+/// Picks the language of the first locale that has a catalog.
+pub fn resolve<'a, I: IntoIterator<Item = Option<&'a str>>>(locales: I) -> &'static str {
+    locales.into_iter().flatten().find_map(language).unwrap_or(FALLBACK_LANGUAGE)
+}
+
+/// Picks the language for messages shown to a single user: their own locale if it is
+/// translated; otherwise the default language of the guild.
+pub async fn user_language(
+    state: &State,
+    guild_id: Option<GuildId>,
+    locale: &str,
+    guild_locale: Option<&str>,
+) -> Result<&'static str, Error> {
+    if let Some(language) = language(locale) {
+        return Ok(language);
+    }
+
+    let settings = match guild_id {
+        Some(guild_id) => state.get_guild_settings(guild_id).await?,
+        None => GuildSettings::default(),
+    };
+    Ok(resolve([settings.language.as_deref(), guild_locale]))
+}
+
+pub async fn command_language(ctx: ApplicationContext<'_>) -> Result<&'static str, Error> {
+    user_language(ctx.data, ctx.guild_id(), &ctx.interaction.locale, ctx.interaction.guild_locale.as_deref()).await
+}
+
+pub fn tr(language: &str, key: &str) -> String {
+    tr_args(language, key, &[])
+}
+
+/// Translates a message, replacing `{name}` placeholders with the given arguments.
+pub fn tr_args(language: &str, key: &str, args: &[(&str, &(dyn Display + Sync))]) -> String {
+    let template = CATALOGS.get(language).and_then(|c| c.messages.get(key))
+        .or_else(|| CATALOGS[FALLBACK_LANGUAGE].messages.get(key));
+
+    let Some(template) = template else {
+        tracing::warn!(key, "missing translation of message");
+        return key.to_owned();
+    };
+
+    args.iter().fold(template.clone(), |acc, (name, value)| acc.replace(&format!("{{{name}}}"), &value.to_string()))
+}
+
+/// Applies the command, parameter and choice translations of all catalogs to the commands.
+pub fn localize_commands(commands: &mut [poise::Command<State, Error>]) {
+    fn localize(command: &mut poise::Command<State, Error>, parent: Option<&str>) {
+        let qualified_name = match parent {
+            Some(parent) => format!("{parent} {}", command.name),
+            None => command.name.clone(),
+        };
+
+        for (&language, catalog) in CATALOGS.iter() {
+            if let Some(localization) = catalog.commands.get(&qualified_name) {
+                if let Some(name) = &localization.name {
+                    command.name_localizations.insert(language.to_owned(), name.clone());
+                }
+                if let Some(description) = &localization.description {
+                    command.description_localizations.insert(language.to_owned(), description.clone());
+                }
+            }
+
+            for parameter in &mut command.parameters {
+                if let Some(name) = catalog.parameter_names.get(&parameter.name) {
+                    parameter.name_localizations.insert(language.to_owned(), name.clone());
+                }
+                if let Some(description) = parameter.description.as_ref().and_then(|d| catalog.parameter_descriptions.get(d)) {
+                    parameter.description_localizations.insert(language.to_owned(), description.clone());
+                }
+                for choice in &mut parameter.choices {
+                    if let Some(name) = catalog.choices.get(&choice.name) {
+                        choice.localizations.insert(language.to_owned(), name.clone());
+                    }
+                }
+            }
+        }
+
+        for subcommand in &mut command.subcommands {
+            localize(subcommand, Some(&qualified_name));
+        }
+    }
+
+    for command in commands {
+        localize(command, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::get_commands;
+
+    use super::{CATALOGS, FALLBACK_LANGUAGE};
+
+    #[test]
+    fn messages_exist_in_fallback() {
+        let fallback = &CATALOGS[FALLBACK_LANGUAGE].messages;
+        for (language, catalog) in CATALOGS.iter() {
+            for key in catalog.messages.keys() {
+                assert!(fallback.contains_key(key), "{language} has unknown message {key}");
+            }
+        }
+    }
+
+    #[test]
+    fn catalogs_are_complete() {
+        fn check(command: &poise::Command<crate::state::State, crate::Error>, parent: Option<&str>) {
+            let qualified_name = match parent {
+                Some(parent) => format!("{parent} {}", command.name),
+                None => command.name.clone(),
+            };
+
+            for (&language, catalog) in CATALOGS.iter().filter(|(&l, _)| l != FALLBACK_LANGUAGE) {
+                for key in CATALOGS[FALLBACK_LANGUAGE].messages.keys() {
+                    assert!(catalog.messages.contains_key(key), "{language} is missing message {key}");
+                }
+
+                let localization = catalog.commands.get(&qualified_name);
+                assert!(localization.and_then(|l| l.name.as_ref()).is_some(), "{language} is missing name of {qualified_name}");
+                assert!(localization.and_then(|l| l.description.as_ref()).is_some(), "{language} is missing description of {qualified_name}");
+
+                for parameter in &command.parameters {
+                    assert!(catalog.parameter_names.contains_key(&parameter.name), "{language} is missing parameter name {}", parameter.name);
+                    let description = parameter.description.as_ref().unwrap();
+                    assert!(catalog.parameter_descriptions.contains_key(description), "{language} is missing parameter description {description:?}");
+                    for choice in &parameter.choices {
+                        assert!(catalog.choices.contains_key(&choice.name), "{language} is missing choice {}", choice.name);
+                    }
+                }
+            }
+
+            for subcommand in &command.subcommands {
+                check(subcommand, Some(&qualified_name));
+            }
+        }
+
+        for command in get_commands().iter().filter(|c| c.slash_action.is_some()) {
+            check(command, None);
+        }
+    }
+}
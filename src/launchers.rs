@@ -0,0 +1,94 @@
+use poise::serenity_prelude::*;
+
+use crate::Error;
+use crate::i18n::{resolve, tr};
+use crate::state::{Form, FormRef, Launcher, Panel, State};
+
+pub fn is_not_found(error: &serenity::Error) -> bool {
+    matches!(error, serenity::Error::Http(e) if e.status_code().map(|s| s.as_u16()) == Some(404))
+}
+
+pub fn components(launcher: &Launcher, form: &Form) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![launcher.button(form)])]
+}
+
+async fn sync_launcher(http: &Http, launcher: &Launcher, form: Option<&Form>) -> serenity::Result<()> {
+    match form {
+        Some(form) => {
+            let edit_message = EditMessage::new().components(components(launcher, form));
+            launcher.channel_id.edit_message(http, launcher.message_id, edit_message).await?;
+        }
+        None => launcher.channel_id.delete_message(http, launcher.message_id).await?,
+    }
+    Ok(())
+}
+
+/// Updates the posted buttons of a form to match it or deletes them if the form no longer exists.
+/// Buttons whose messages have been deleted are forgotten.
+async fn sync_launchers(http: &Http, state: &State, form_ref: FormRef, form: Option<&Form>) -> Result<(), Error> {
+    for launcher in state.get_launchers(form_ref).await? {
+        let result = sync_launcher(http, &launcher, form).await;
+
+        if let Err(e) = &result {
+            if !is_not_found(e) {
+                tracing::warn!(error = ?e, message_id = %launcher.message_id, "Failed to update form button");
+                continue;
+            }
+        }
+
+        if form.is_none() || result.is_err() {
+            state.delete_launcher(form_ref, launcher.message_id).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the embed and components of a panel's message.
+pub async fn panel_contents(state: &State, guild_id: GuildId, panel: &Panel) -> Result<(CreateEmbed, Vec<CreateActionRow>), Error> {
+    let forms = state.get_forms(guild_id).await?;
+    let settings = state.get_guild_settings(guild_id).await?;
+    let placeholder = tr(resolve([settings.language.as_deref()]), "select-form");
+    Ok((panel.embed(), panel.components(&forms, &placeholder)))
+}
+
+/// Updates the message of a panel. Returns false if the message has been deleted.
+pub async fn update_panel(http: &Http, state: &State, guild_id: GuildId, panel: &Panel) -> Result<bool, Error> {
+    let (embed, components) = panel_contents(state, guild_id, panel).await?;
+    let edit_message = EditMessage::new().embed(embed).components(components);
+
+    match panel.channel_id.edit_message(http, panel.message_id, edit_message).await {
+        Ok(_) => Ok(true),
+        Err(e) if is_not_found(&e) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Updates the panels containing a form, removing the form from them if it no longer exists.
+/// Panels whose messages have been deleted are forgotten.
+async fn sync_panels(http: &Http, state: &State, form_ref: FormRef, deleted: bool) -> Result<(), Error> {
+    for mut panel in state.get_panels(form_ref.guild_id).await? {
+        if !panel.contains(form_ref.form_id) {
+            continue;
+        }
+
+        if deleted {
+            panel.remove_entry(form_ref.form_id);
+            state.save_panel(form_ref.guild_id, &panel).await?;
+        }
+
+        match update_panel(http, state, form_ref.guild_id, &panel).await {
+            Ok(true) => {}
+            Ok(false) => { state.delete_panel(form_ref.guild_id, panel.id()).await?; }
+            Err(e) => tracing::warn!(error = ?e, message_id = %panel.message_id, "Failed to update panel"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Updates every button and panel of a form after it has been changed or deleted.
+pub async fn sync_form(http: &Http, state: &State, form_ref: FormRef, form: Option<&Form>) -> Result<(), Error> {
+    sync_launchers(http, state, form_ref, form).await?;
+    sync_panels(http, state, form_ref, form.is_none()).await
+}
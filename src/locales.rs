@@ -0,0 +1,78 @@
+/// Locales supported by Discord along with their native names.
+pub const DISCORD_LOCALES: [(&str, &str); 32] = [
+    ("id", "Bahasa Indonesia"),
+    ("da", "Dansk"),
+    ("de", "Deutsch"),
+    ("en-GB", "English, UK"),
+    ("en-US", "English, US"),
+    ("es-ES", "Español"),
+    ("es-419", "Español, LATAM"),
+    ("fr", "Français"),
+    ("hr", "Hrvatski"),
+    ("it", "Italiano"),
+    ("lt", "Lietuviškai"),
+    ("hu", "Magyar"),
+    ("nl", "Nederlands"),
+    ("no", "Norsk"),
+    ("pl", "Polski"),
+    ("pt-BR", "Português do Brasil"),
+    ("ro", "Română"),
+    ("fi", "Suomi"),
+    ("sv-SE", "Svenska"),
+    ("vi", "Tiếng Việt"),
+    ("tr", "Türkçe"),
+    ("cs", "Čeština"),
+    ("el", "Ελληνικά"),
+    ("bg", "български"),
+    ("ru", "Pусский"),
+    ("uk", "Українська"),
+    ("hi", "हिन्दी"),
+    ("th", "ไทย"),
+    ("zh-CN", "中文"),
+    ("ja", "日本語"),
+    ("zh-TW", "繁體中文"),
+    ("ko", "한국어"),
+];
+
+pub fn is_supported(locale: &str) -> bool {
+    DISCORD_LOCALES.iter().any(|(l, _)| *l == locale)
+}
+
+pub fn locale_name(locale: &str) -> &str {
+    DISCORD_LOCALES.iter()
+        .find(|(l, _)| *l == locale)
+        .map(|(_, name)| *name)
+        .unwrap_or(locale)
+}
+
+fn language(locale: &str) -> &str {
+    locale.split('-').next().unwrap_or(locale)
+}
+
+/// Returns the keys of `available` in the order they should be tried for `locale`:
+/// an exact match first, then locales sharing the same language (e.g. `en-GB` for `en-US`).
+pub fn candidates<'a, I: IntoIterator<Item = &'a str>>(locale: &str, available: I) -> Vec<&'a str> {
+    let available = available.into_iter().collect::<Vec<_>>();
+    let exact = available.iter().copied().filter(|l| *l == locale);
+    let related = available.iter().copied().filter(|l| *l != locale && language(l) == language(locale));
+    exact.chain(related).collect()
+}
+
+pub fn same_language(a: &str, b: &str) -> bool {
+    language(a) == language(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::candidates;
+
+    #[test]
+    fn exact_match_first() {
+        assert_eq!(candidates("en-US", ["en-GB", "da", "en-US"]), ["en-US", "en-GB"]);
+    }
+
+    #[test]
+    fn no_match() {
+        assert!(candidates("fr", ["en-GB", "da"]).is_empty());
+    }
+}
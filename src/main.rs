@@ -1,15 +1,31 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use poise::serenity_prelude as serenity;
 
 use crate::commands::get_commands;
 use crate::errors::on_error;
 use crate::event_handler::event_handler;
+use crate::metrics::Metrics;
+use crate::shutdown::{Shutdown, wait_for_signal};
 use crate::state::State;
 
 mod commands;
 mod event_handler;
+mod filter;
 mod state;
 mod responses;
 mod errors;
+mod i18n;
+mod launchers;
+mod locales;
+mod metrics;
+mod modals;
+mod relay;
+mod shutdown;
+mod stats;
+mod submissions;
+mod transcripts;
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type ApplicationContext<'a> = poise::ApplicationContext<'a, State, Error>;
@@ -18,14 +34,25 @@ type FrameworkContext<'a> = poise::FrameworkContext<'a, State, Error>;
 type Context<'a> = poise::Context<'a, State, Error>;
 type FrameworkError<'a> = poise::FrameworkError<'a, State, Error>;
 
-async fn setup(ctx: &serenity::Context, _: &serenity::Ready, framework: &Framework) -> Result<State, Error> {
-    poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+/// How long to wait for in-flight submissions when shutting down.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
 
-    let redis_url = std::env::var("REDIS_URL").expect("please provide REDIS_URL");
-    let redis_client = redis::Client::open(redis_url).expect("failed to connect to redis");
-    let connection_manager = redis_client.get_connection_manager().await.expect("failed to setup redis connection manager");
+async fn reject_during_shutdown(ctx: Context<'_>) -> Result<bool, Error> {
+    if !ctx.data().shutdown.is_shutting_down() {
+        return Ok(true);
+    }
 
-    Ok(State { connection_manager })
+    let language = match ctx {
+        poise::Context::Application(ctx) => i18n::command_language(ctx).await?,
+        poise::Context::Prefix(_) => i18n::FALLBACK_LANGUAGE,
+    };
+    ctx.say(i18n::tr(language, "shutting-down")).await?;
+    Ok(false)
+}
+
+async fn setup(ctx: &serenity::Context, _: &serenity::Ready, framework: &Framework, state: State) -> Result<State, Error> {
+    poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+    Ok(state)
 }
 
 #[tokio::main]
@@ -33,18 +60,59 @@ async fn main() {
     env_logger::init();
 
     let token = std::env::var("DISCORD_TOKEN").expect("please provide DISCORD_TOKEN");
+    let redis_url = std::env::var("REDIS_URL").expect("please provide REDIS_URL");
+    let http_address = std::env::var("HTTP_ADDRESS").ok()
+        .map(|a| a.parse().expect("HTTP_ADDRESS must be a socket address (e.g. 0.0.0.0:8080)"));
+
+    let redis_client = redis::Client::open(redis_url).expect("failed to connect to redis");
+    let connection_manager = redis_client.get_connection_manager().await.expect("failed to setup redis connection manager");
+    let state = State {
+        connection_manager,
+        metrics: Arc::new(Metrics::new()),
+        shutdown: Arc::new(Shutdown::default()),
+    };
+
+    let framework_state = state.clone();
     let framework = poise::Framework::new(
         poise::FrameworkOptions {
             commands: get_commands(),
             on_error: |error| Box::pin(on_error(error)),
+            command_check: Some(|ctx| Box::pin(reject_during_shutdown(ctx))),
             event_handler: |ctx, event, framework, _| Box::pin(event_handler(ctx, event, framework)),
             ..Default::default()
         },
-        |ctx, ready, framework| Box::pin(setup(ctx, ready, framework)));
+        |ctx, ready, framework| Box::pin(setup(ctx, ready, framework, framework_state)));
 
     let mut client = serenity::Client::builder(token, serenity::GatewayIntents::non_privileged())
         .framework(framework)
         .await.expect("failed to build client");
 
+    if let Some(address) = http_address {
+        let state = state.clone();
+        let shard_manager = client.shard_manager.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(address, state, shard_manager).await {
+                tracing::error!(error = ?e, "HTTP server failed");
+            }
+        });
+    }
+
+    tokio::spawn(responses::retry_failed_submissions(client.http.clone(), state.clone()));
+
+    let shutdown_state = state.clone();
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        wait_for_signal().await;
+        tracing::info!("Shutting down; waiting for in-flight submissions");
+        if !shutdown_state.shutdown.drain(SHUTDOWN_TIMEOUT).await {
+            tracing::warn!("Timed out waiting for in-flight submissions");
+        }
+        shard_manager.shutdown_all().await;
+    });
+
     client.start().await.expect("failed running client");
+
+    if let Err(e) = state.close().await {
+        tracing::warn!(error = ?e, "Failed to close Redis connection");
+    }
 }
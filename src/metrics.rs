@@ -0,0 +1,166 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use hyper::service::{make_service_fn, service_fn};
+use poise::serenity_prelude::{ConnectionStage, ShardManager};
+use prometheus::{Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry, TextEncoder};
+use redis::{Cmd, Pipeline, RedisFuture, Value};
+use redis::aio::{ConnectionLike, ConnectionManager};
+
+use crate::state::State;
+
+pub struct Metrics {
+    registry: Registry,
+    pub modals_opened: IntCounter,
+    pub submissions_created: IntCounter,
+    pub submissions_failed: IntCounter,
+    pub validation_failures: IntCounterVec,
+    pub cooldown_rejections: IntCounter,
+    pub filtered_submissions: IntCounterVec,
+    pub command_errors: IntCounterVec,
+    pub redis_latency: Histogram,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("formsbot".to_owned()), None)
+            .expect("failed to create metrics registry");
+
+        let modals_opened = IntCounter::new("modals_opened_total", "Forms shown to users")
+            .expect("failed to create metric");
+        let submissions_created = IntCounter::new("submissions_created_total", "Submissions posted to threads")
+            .expect("failed to create metric");
+        let submissions_failed = IntCounter::new("submissions_failed_total", "Submissions which could not be posted and were queued for retry")
+            .expect("failed to create metric");
+        let validation_failures = IntCounterVec::new(
+            Opts::new("validation_failures_total", "Forms which could not be opened or commands given invalid input"),
+            &["reason"],
+        ).expect("failed to create metric");
+        let cooldown_rejections = IntCounter::new("cooldown_rejections_total", "Users rejected due to cooldowns")
+            .expect("failed to create metric");
+        let filtered_submissions = IntCounterVec::new(
+            Opts::new("filtered_submissions_total", "Submissions containing blocked terms"),
+            &["action"],
+        ).expect("failed to create metric");
+        let command_errors = IntCounterVec::new(
+            Opts::new("command_errors_total", "Errors occurring while handling commands"),
+            &["command"],
+        ).expect("failed to create metric");
+        let redis_latency = Histogram::with_opts(HistogramOpts::new("redis_latency_seconds", "Latency of Redis requests")
+            .buckets(vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]))
+            .expect("failed to create metric");
+
+        registry.register(Box::new(modals_opened.clone())).expect("failed to register metric");
+        registry.register(Box::new(submissions_created.clone())).expect("failed to register metric");
+        registry.register(Box::new(submissions_failed.clone())).expect("failed to register metric");
+        registry.register(Box::new(validation_failures.clone())).expect("failed to register metric");
+        registry.register(Box::new(cooldown_rejections.clone())).expect("failed to register metric");
+        registry.register(Box::new(filtered_submissions.clone())).expect("failed to register metric");
+        registry.register(Box::new(command_errors.clone())).expect("failed to register metric");
+        registry.register(Box::new(redis_latency.clone())).expect("failed to register metric");
+
+        Metrics {
+            registry,
+            modals_opened,
+            submissions_created,
+            submissions_failed,
+            validation_failures,
+            cooldown_rejections,
+            filtered_submissions,
+            command_errors,
+            redis_latency,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer).expect("failed to encode metrics");
+        buffer
+    }
+}
+
+/// A Redis connection which records the latency of every request.
+#[derive(Clone)]
+pub struct TimedConnection {
+    inner: ConnectionManager,
+    metrics: Arc<Metrics>,
+}
+
+impl TimedConnection {
+    pub fn new(inner: ConnectionManager, metrics: Arc<Metrics>) -> Self {
+        TimedConnection { inner, metrics }
+    }
+}
+
+impl ConnectionLike for TimedConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(async move {
+            let _timer = self.metrics.redis_latency.start_timer();
+            self.inner.req_packed_command(cmd).await
+        })
+    }
+
+    fn req_packed_commands<'a>(&'a mut self, cmd: &'a Pipeline, offset: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(async move {
+            let _timer = self.metrics.redis_latency.start_timer();
+            self.inner.req_packed_commands(cmd, offset, count).await
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        self.inner.get_db()
+    }
+}
+
+async fn health(state: &State, shard_manager: &ShardManager) -> Result<(), String> {
+    if state.shutdown.is_shutting_down() {
+        return Err("shutting down".to_owned());
+    }
+
+    {
+        let runners = shard_manager.runners.lock().await;
+        if runners.is_empty() {
+            return Err("no shards are running".to_owned());
+        }
+
+        if let Some((id, runner)) = runners.iter().find(|(_, r)| r.stage != ConnectionStage::Connected) {
+            return Err(format!("shard {id} is {}", runner.stage));
+        }
+    }
+
+    state.ping().await.map_err(|e| format!("redis is unavailable: {e}"))
+}
+
+async fn handle(request: Request<Body>, state: State, shard_manager: Arc<ShardManager>) -> Result<Response<Body>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .header(hyper::header::CONTENT_TYPE, TextEncoder::new().format_type())
+            .body(Body::from(state.metrics.encode())),
+        (&Method::GET, "/healthz") => match health(&state, &shard_manager).await {
+            Ok(()) => Response::builder().body(Body::from("ok")),
+            Err(reason) => Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .body(Body::from(reason)),
+        },
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty()),
+    };
+
+    Ok(response.expect("failed to build response"))
+}
+
+/// Serves `/metrics` and `/healthz` until the process exits.
+pub async fn serve(address: SocketAddr, state: State, shard_manager: Arc<ShardManager>) -> Result<(), hyper::Error> {
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        let shard_manager = shard_manager.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| handle(request, state.clone(), shard_manager.clone())))
+        }
+    });
+
+    Server::bind(&address).serve(make_service).await
+}
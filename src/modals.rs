@@ -0,0 +1,115 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use poise::serenity_prelude::{ActionRowComponent, ModalInteractionData};
+
+use crate::state::FormId;
+
+const CUSTOM_ID_PREFIX: &str = "form_modal:";
+
+/// What happens when a form modal is submitted.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ModalKind {
+    /// A user filling in the form; the answers are posted if cooldowns and quotas allow it.
+    Submit,
+    /// A moderator trying the form through `/forms show`; the answers are posted regardless.
+    Test,
+    /// A moderator previewing the form through `/forms show`; the answers are discarded.
+    Preview,
+}
+
+impl ModalKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ModalKind::Submit => "submit",
+            ModalKind::Test => "test",
+            ModalKind::Preview => "preview",
+        }
+    }
+}
+
+/// The custom id of a form modal.
+///
+/// It carries everything needed to handle the submission, so it can be handled by any process
+/// (even one started after the modal was shown) rather than waiting for it in memory. The revision
+/// is that of the form's fields when the modal was shown, so answers are never matched up with
+/// fields which have since been added, removed or moved.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ModalId {
+    pub kind: ModalKind,
+    pub form_id: FormId,
+    pub revision: u32,
+    /// The page of the form shown in the modal, counting from 0.
+    pub page: u8,
+}
+
+impl Display for ModalId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{CUSTOM_ID_PREFIX}{}:{}:{}:{}", self.kind.as_str(), self.form_id, self.revision, self.page)
+    }
+}
+
+impl FromStr for ModalId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.strip_prefix(CUSTOM_ID_PREFIX).ok_or(())?.splitn(4, ':');
+        let kind = match parts.next() {
+            Some("submit") => ModalKind::Submit,
+            Some("test") => ModalKind::Test,
+            Some("preview") => ModalKind::Preview,
+            _ => return Err(()),
+        };
+        let form_id = parts.next().and_then(|id| id.parse().ok()).ok_or(())?;
+        let revision = parts.next().and_then(|r| r.parse().ok()).ok_or(())?;
+        // Modals shown before forms had pages have no page
+        let page = match parts.next() {
+            Some(page) => page.parse().map_err(|_| ())?,
+            None => 0,
+        };
+        Ok(ModalId { kind, form_id, revision, page })
+    }
+}
+
+/// Collects the answers of a submitted modal of a form, by the index of the field they answer.
+pub fn indexed_answers(data: &ModalInteractionData) -> Vec<(usize, String)> {
+    data.components.iter()
+        .flat_map(|row| &row.components)
+        .filter_map(|component| match component {
+            ActionRowComponent::InputText(input) => Some((input.custom_id.parse().ok()?, input.value.clone().unwrap_or_default())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects the answers of a submitted modal in the order of its fields.
+pub fn answers(data: &ModalInteractionData) -> Vec<String> {
+    data.components.iter()
+        .flat_map(|row| &row.components)
+        .filter_map(|component| match component {
+            ActionRowComponent::InputText(input) => Some(input.value.clone().unwrap_or_default()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ModalId, ModalKind};
+
+    #[test]
+    fn custom_id_round_trip() {
+        for kind in [ModalKind::Submit, ModalKind::Test, ModalKind::Preview] {
+            let id = ModalId { kind, form_id: "67e55044-10b1-426f-9247-bb680e5fe0c8".parse().unwrap(), revision: 7, page: 2 };
+            let custom_id = id.to_string();
+            assert!(custom_id.len() <= 100);
+            assert_eq!(custom_id.parse(), Ok(id));
+        }
+
+        assert_eq!("show_form:67e55044-10b1-426f-9247-bb680e5fe0c8".parse::<ModalId>(), Err(()));
+        assert_eq!("form_modal:submit:67e55044-10b1-426f-9247-bb680e5fe0c8".parse::<ModalId>(), Err(()));
+
+        let legacy = "form_modal:test:67e55044-10b1-426f-9247-bb680e5fe0c8:3".parse::<ModalId>().unwrap();
+        assert_eq!((legacy.kind, legacy.revision, legacy.page), (ModalKind::Test, 3, 0));
+    }
+}
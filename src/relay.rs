@@ -0,0 +1,112 @@
+use poise::serenity_prelude::*;
+
+use crate::{Error, FrameworkContext, filter};
+use crate::filter::{Incident, IncidentKind};
+use crate::i18n::{FALLBACK_LANGUAGE, resolve, tr, tr_args};
+use crate::responses::guild_locale;
+use crate::state::{FilterAction, Form, State, SubmissionRecord};
+
+/// The title of relayed messages, so the submitter can tell their conversations apart.
+fn conversation_title(form: Option<&Form>, record: &SubmissionRecord, locale: &str) -> String {
+    let submission = &record.submission;
+    let title = form.map_or_else(|| tr(resolve([Some(locale)]), "deleted-form"), |f| f.localized_title(locale).to_owned());
+    match submission.ticket() {
+        Some(ticket) => format!("{ticket} {title}"),
+        None => title,
+    }
+}
+
+/// Sends a message from staff to the submitter of a relayed submission.
+/// Returns whether it could be delivered.
+pub async fn send_to_submitter(http: &Http, state: &State, record: &SubmissionRecord, message: &str) -> Result<bool, Error> {
+    let submission = &record.submission;
+    let language = resolve([Some(submission.locale.as_str())]);
+    let form = state.get_form(submission.form_ref()).await?;
+
+    let embed = CreateEmbed::new()
+        .title(conversation_title(form.as_ref(), record, &submission.locale))
+        .description(message)
+        .footer(CreateEmbedFooter::new(tr(language, "relay-reply-hint")))
+        .timestamp(Timestamp::now());
+
+    if let Err(e) = submission.user_id.direct_message(http, CreateMessage::new().embed(embed)).await {
+        tracing::warn!(error = ?e, id = %submission.id, "Failed to relay message to submitter");
+        return Ok(false);
+    }
+
+    // Answers go to the conversation staff most recently wrote in
+    state.set_relay(submission).await?;
+    Ok(true)
+}
+
+/// Posts a direct message from a submitter to the thread of their relayed submission.
+pub async fn forward_direct_message(ctx: &Context, message: &Message, framework: FrameworkContext<'_>) -> Result<(), Error> {
+    let state = framework.user_data;
+    let _in_flight = state.shutdown.track();
+
+    // Users who never had a conversation with staff are ignored, so the bot cannot be made to spam replies
+    let Some((guild_id, id)) = state.get_relay(message.author.id).await? else {
+        return Ok(());
+    };
+    let record = state.get_submission(guild_id, id).await?;
+    let Some(record) = record.filter(|r| r.submission.relay && !r.withdrawn && !r.closed) else {
+        message.reply(ctx, tr(FALLBACK_LANGUAGE, "relay-no-conversation")).await?;
+        return Ok(());
+    };
+
+    let submission = &record.submission;
+    let guild_locale = guild_locale(state, submission).await?;
+    let language = resolve([Some(submission.locale.as_str())]);
+    let form = state.get_form(submission.form_ref()).await?;
+
+    // Relayed messages end up next to the submission, so they are held to the same filters
+    let settings = state.get_guild_settings(submission.guild_id).await?;
+    let filters = [Some(&settings.word_filter), form.as_ref().map(|f| &f.word_filter)];
+    let mut content = message.content.clone();
+    let mut flagged = false;
+    if let Some(found) = filter::check_answers(&filters.into_iter().flatten().collect::<Vec<_>>(), std::slice::from_ref(&content)) {
+        match &form {
+            Some(form) => {
+                let incident = Incident { kind: IncidentKind::RelayedMessage, user: &message.author, guild_locale: &guild_locale, found: &found };
+                filter::report_incident(&ctx.http, state, &settings, form, incident).await?;
+            }
+            None => tracing::warn!(user = %message.author.id, action = ?found.action, terms = ?found.terms, "Blocked terms found in a message about a deleted form"),
+        }
+
+        if found.action == FilterAction::Reject {
+            message.reply(ctx, tr(language, "relay-blocked")).await?;
+            return Ok(());
+        }
+        content = found.redacted.into_iter().next().unwrap_or_default();
+        flagged = true;
+    }
+
+    let mut embed = CreateEmbed::new()
+        .description(content)
+        .timestamp(message.timestamp);
+    if flagged {
+        embed = embed.footer(CreateEmbedFooter::new(tr(resolve([Some(guild_locale.as_str())]), "submission-flagged-marker")));
+    }
+    embed = if submission.anonymous {
+        embed.author(CreateEmbedAuthor::new(tr(resolve([Some(guild_locale.as_str())]), "relay-submitter")))
+    } else {
+        embed.author(CreateEmbedAuthor::new(&submission.user_name).icon_url(message.author.face()))
+    };
+
+    let attachments = message.attachments.iter().map(|a| a.url.as_str()).collect::<Vec<_>>();
+    if !attachments.is_empty() {
+        let name = tr(resolve([Some(guild_locale.as_str())]), "relay-attachments");
+        embed = embed.field(name, attachments.join("\n"), false);
+    }
+
+    match record.thread_id.send_message(ctx, CreateMessage::new().embed(embed)).await {
+        Ok(_) => message.react(ctx, '✅').await.map(|_| ())?,
+        Err(e) => {
+            tracing::warn!(error = ?e, id = %submission.id, "Failed to relay message to thread");
+            let title = conversation_title(form.as_ref(), &record, &submission.locale);
+            message.reply(ctx, tr_args(language, "relay-forward-failed", &[("title", &title)])).await?;
+        }
+    }
+
+    Ok(())
+}
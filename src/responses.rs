@@ -1,32 +1,270 @@
-use serenity::all::ChannelType;
-use serenity::builder::{CreateEmbed, CreateEmbedAuthor, CreateMessage, CreateThread, EditInteractionResponse};
-use serenity::model::channel::AutoArchiveDuration;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::all::{ButtonStyle, ChannelType, MessageId};
+use serenity::builder::{CreateActionRow, CreateAttachment, CreateButton, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateMessage, CreateThread, EditAttachments, EditInteractionResponse, EditMessage, EditThread};
+use serenity::http::Http;
+use serenity::model::application::ModalInteraction;
+use serenity::model::channel::{AutoArchiveDuration, GuildChannel};
 use serenity::model::Timestamp;
 use serenity::prelude::*;
-use serenity::utils::QuickModalResponse;
 
 use crate::Error;
-use crate::state::Form;
+use crate::i18n::{FALLBACK_LANGUAGE, resolve, tr, tr_args};
+use crate::locales::{locale_name, same_language};
+use crate::state::{FailedSubmission, Form, FormRef, QuizResult, State, Submission, SubmissionId, SubmissionRecord};
+
+/// How often submissions which could not be posted are retried.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+pub const EDIT_CUSTOM_ID_PREFIX: &str = "submission_edit:";
+pub const WITHDRAW_CUSTOM_ID_PREFIX: &str = "submission_withdraw:";
+pub const CLOSE_CUSTOM_ID_PREFIX: &str = "submission_close:";
+
+/// The most characters Discord allows across all embeds of a message.
+const MESSAGE_EMBEDS_MAX_LENGTH: usize = 6000;
+const MESSAGE_MAX_EMBEDS: usize = 10;
+pub const EMBED_FIELD_MAX_LENGTH: usize = 1024;
+const ANSWERS_FILE_NAME: &str = "answers.txt";
+
+/// The locale a submission is posted in: the server's language if set, otherwise its Discord locale.
+pub async fn guild_locale(state: &State, submission: &Submission) -> Result<String, Error> {
+    let settings = state.get_guild_settings(submission.guild_id).await?;
+    Ok(settings.language
+        .or_else(|| submission.guild_locale.clone())
+        .unwrap_or_else(|| FALLBACK_LANGUAGE.to_owned()))
+}
+
+/// Shortens an answer to at most `max_length` characters (plus an ellipsis), showing empty ones as "-".
+pub fn truncate_answer(answer: &str, max_length: usize) -> String {
+    match answer.char_indices().nth(max_length) {
+        Some((i, _)) => format!("{}…", &answer[..i]),
+        None if answer.is_empty() => "-".to_owned(),
+        None => answer.to_owned(),
+    }
+}
+
+/// Shortens the answers shown as the fields of a single embed, given the name and value of every
+/// answer, so that they fit in a field each and the embed stays within Discord's limit on the length
+/// of a message. `overhead` is the length of the rest of the embed.
+pub fn truncate_answers(answers: &[(String, &str)], overhead: usize) -> Vec<String> {
+    // Every answer keeps at least one character: an ellipsis, or "-" if it is empty
+    let mut budget = MESSAGE_EMBEDS_MAX_LENGTH.saturating_sub(overhead + answers.iter().map(|(name, _)| name.chars().count() + 1).sum::<usize>());
+    answers.iter().map(|(_, answer)| {
+        let answer = truncate_answer(answer, (EMBED_FIELD_MAX_LENGTH - 1).min(budget));
+        budget -= answer.chars().count() - 1;
+        answer
+    }).collect()
+}
+
+/// Where an answer is shown in the message of a submission.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Placement {
+    /// A field of the main embed.
+    Field,
+    /// An embed of its own, as it is too long for a field.
+    Embed,
+    /// The attached file, as there is no room left in the embeds of the message.
+    File,
+}
+
+/// Decides where each answer is shown, given the lengths of the name and value of every answer.
+///
+/// Answers which fit in an embed field go in one and longer ones get an embed of their own, as long
+/// as the message stays within Discord's limits; whatever does not fit is replaced by a short note
+/// (`note_length` long) and left to an attached file. `overhead` is the length of the rest of the
+/// main embed.
+fn place_answers(answers: &[(usize, usize)], overhead: usize, note_length: usize) -> Vec<Placement> {
+    let mut budget = MESSAGE_EMBEDS_MAX_LENGTH.saturating_sub(overhead + answers.iter().map(|(name, _)| name + note_length).sum::<usize>());
+    let mut embeds = 1;
+
+    answers.iter().map(|&(_, value)| {
+        let placement = if value <= EMBED_FIELD_MAX_LENGTH { Placement::Field } else { Placement::Embed };
+        let extra = value.saturating_sub(note_length);
+        if extra > budget || (placement == Placement::Embed && embeds == MESSAGE_MAX_EMBEDS) {
+            return Placement::File;
+        }
 
-pub async fn create_response(ctx: &Context, form: &Form, response: QuickModalResponse) -> Result<(), Error> {
-    response.interaction.defer_ephemeral(ctx).await?;
+        budget -= extra;
+        if placement == Placement::Embed {
+            embeds += 1;
+        }
+        placement
+    }).collect()
+}
 
-    let member = response.interaction.member.as_ref().expect("can only be run in guild");
-    let user_name = member.display_name();
+/// The embeds and attached file showing the answers of a submission.
+pub struct SubmissionMessage {
+    pub embeds: Vec<CreateEmbed>,
+    pub file: Option<CreateAttachment>,
+}
 
-    let create_thread = CreateThread::new(user_name)
+/// Describes the score of a quiz submission and whether it passed, e.g. "Score: 4/5 • Passed".
+pub fn quiz_summary(language: &str, result: &QuizResult) -> String {
+    let mut summary = tr_args(language, "quiz-score", &[("points", &result.points), ("max", &result.max)]);
+    let verdict = match (result.passed, result.decided) {
+        (Some(true), true) => Some("quiz-accepted-marker"),
+        (Some(false), true) => Some("quiz-denied-marker"),
+        (Some(true), false) => Some("quiz-passed-marker"),
+        (Some(false), false) => Some("quiz-failed-marker"),
+        (None, _) => None,
+    };
+    if let Some(verdict) = verdict {
+        summary += " • ";
+        summary += &tr(language, verdict);
+    }
+    summary
+}
+
+/// Builds the embeds showing the answers of a submission, along with a file for the answers which
+/// do not fit in them.
+pub fn submission_message(form: &Form, record: &SubmissionRecord, guild_locale: &str) -> SubmissionMessage {
+    let submission = &record.submission;
+    let guild_language = resolve([Some(guild_locale)]);
+
+    let localized_title = form.localized_title(guild_locale);
+    let title = submission.ticket().map_or_else(|| localized_title.to_owned(), |ticket| format!("{ticket} {localized_title}"));
+    let mut overhead = title.chars().count();
+    let mut embed_builder = CreateEmbed::new()
+        .title(title)
+        .timestamp(submission.submitted_at);
+
+    let mut footer = vec![];
+    if submission.flagged {
+        footer.push(tr(guild_language, "submission-flagged-marker"));
+    }
+    if submission.anonymous {
+        footer.push(tr_args(guild_language, "anonymous-submission-id", &[("id", &submission.id)]));
+    } else {
+        overhead += submission.user_name.chars().count();
+        embed_builder = embed_builder.author(CreateEmbedAuthor::new(&submission.user_name).icon_url(&submission.avatar_url));
+    }
+
+    if let Some(result) = &submission.quiz {
+        let summary = quiz_summary(guild_language, result);
+        overhead += summary.chars().count();
+        embed_builder = embed_builder.description(summary);
+    }
+    if !same_language(&submission.locale, guild_locale) {
+        footer.push(tr_args(guild_language, "submitted-in", &[("language", &locale_name(&submission.locale))]));
+    }
+    if !record.history.is_empty() {
+        footer.push(tr_args(guild_language, "submission-edited-marker", &[("count", &record.history.len())]));
+    }
+    if record.withdrawn {
+        footer.push(tr(guild_language, "submission-withdrawn-marker"));
+    }
+    if record.closed {
+        footer.push(tr(guild_language, "submission-closed-marker"));
+    }
+    if !footer.is_empty() {
+        let footer = footer.join(" • ");
+        overhead += footer.chars().count();
+        embed_builder = embed_builder.footer(CreateEmbedFooter::new(footer));
+    }
+
+    let note = tr(guild_language, "answer-in-file");
+    // Fields which were hidden by their conditions were not answered
+    let answers_by_field = form.answers_by_field(&submission.answers);
+    let answers = form.fields().iter().zip(&submission.answers).enumerate()
+        .filter(|(i, _)| form.is_shown(*i, &answers_by_field))
+        .map(|(_, answer)| answer)
+        .collect::<Vec<_>>();
+    let lengths = answers.iter()
+        .map(|(field, value)| (field.localized_name(guild_locale).chars().count(), value.chars().count()))
+        .collect::<Vec<_>>();
+    let placements = place_answers(&lengths, overhead, note.chars().count());
+
+    let mut embeds = vec![];
+    let mut file = String::new();
+    for ((field, value), placement) in answers.into_iter().zip(placements) {
+        match placement {
+            Placement::Field => embed_builder = field.apply_to_embed(embed_builder, value.clone(), guild_locale),
+            Placement::Embed => embeds.push(CreateEmbed::new().title(field.localized_name(guild_locale)).description(value)),
+            Placement::File => {
+                embed_builder = field.apply_to_embed(embed_builder, note.clone(), guild_locale);
+                file += &format!("{}:\n{value}\n\n", field.localized_name(guild_locale));
+            }
+        }
+    }
+    embeds.insert(0, embed_builder);
+
+    SubmissionMessage {
+        embeds,
+        file: Some(file).filter(|f| !f.is_empty()).map(|f| CreateAttachment::bytes(f, ANSWERS_FILE_NAME)),
+    }
+}
+
+/// Updates the message of a submission after it has changed.
+/// The answers are only shown again if the form still exists.
+pub fn edit_submission_message(form: Option<&Form>, record: &SubmissionRecord, guild_locale: &str) -> EditMessage {
+    let mut edit_message = EditMessage::new().components(submission_components(record, guild_locale));
+    if let Some(form) = form {
+        let SubmissionMessage { embeds, file } = submission_message(form, record, guild_locale);
+        let attachments = file.into_iter().fold(EditAttachments::new(), EditAttachments::add);
+        edit_message = edit_message.embeds(embeds).attachments(attachments);
+    }
+    edit_message
+}
+
+/// The buttons letting the submitter change their mind (if they are in the thread and did not
+/// withdraw) and letting staff close the submission, until it is closed.
+pub fn submission_components(record: &SubmissionRecord, guild_locale: &str) -> Vec<CreateActionRow> {
+    if record.closed {
+        return vec![];
+    }
+
+    let guild_language = resolve([Some(guild_locale)]);
+    let id = record.submission.id;
+    let mut buttons = vec![];
+    if !record.withdrawn && record.submission.in_thread() {
+        buttons.push(CreateButton::new(format!("{EDIT_CUSTOM_ID_PREFIX}{id}"))
+            .label(tr(guild_language, "edit-answers"))
+            .style(ButtonStyle::Secondary));
+        buttons.push(CreateButton::new(format!("{WITHDRAW_CUSTOM_ID_PREFIX}{id}"))
+            .label(tr(guild_language, "withdraw"))
+            .style(ButtonStyle::Danger));
+    }
+    buttons.push(CreateButton::new(format!("{CLOSE_CUSTOM_ID_PREFIX}{id}"))
+        .label(tr(guild_language, "close"))
+        .style(ButtonStyle::Secondary));
+
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+/// Posts a submission to a new private thread under the destination of the form.
+pub async fn post_submission(http: &Http, state: &State, form: &Form, submission: &Submission) -> Result<GuildChannel, Error> {
+    let guild_locale = guild_locale(state, submission).await?;
+    let mut thread_name = if submission.anonymous {
+        tr(resolve([Some(guild_locale.as_str())]), "anonymous-thread-name")
+    } else {
+        submission.user_name.clone()
+    };
+    if let Some(ticket) = submission.ticket() {
+        thread_name = format!("{ticket} {thread_name}");
+    }
+
+    let create_thread = CreateThread::new(thread_name)
         .kind(ChannelType::PrivateThread)
         .auto_archive_duration(AutoArchiveDuration::OneWeek)
         .invitable(false);
-    let thread = form.destination.create_thread(ctx, create_thread).await?;
+    let thread = form.destination.create_thread(http, create_thread).await?;
 
-    let mut embed_builder = CreateEmbed::new()
-        .title(form.title())
-        .timestamp(Timestamp::now())
-        .author(CreateEmbedAuthor::new(user_name).icon_url(member.face()));
-
-    embed_builder = form.fields().iter().zip(response.inputs.into_iter())
-        .fold(embed_builder, |acc, (field, value)| field.apply_to_embed(acc, value));
+    let mut record = SubmissionRecord {
+        submission: submission.clone(),
+        thread_id: thread.id,
+        // Set once the message has been sent
+        message_id: MessageId::default(),
+        history: vec![],
+        withdrawn: false,
+        closed: false,
+        closed_at: None,
+    };
+    // Quiz submissions which were decided automatically need no attention from staff
+    if submission.quiz.is_some_and(|q| q.decided) {
+        record.closed = true;
+        record.closed_at = Some(submission.submitted_at);
+    }
 
     let mut content = None;
 
@@ -34,20 +272,235 @@ pub async fn create_response(ctx: &Context, form: &Form, response: QuickModalRes
         content = Some(mentionable.to_string() + "\n");
     }
 
-    if let Some(description) = form.description() {
+    if let Some(description) = form.localized_description(&guild_locale) {
         *content.get_or_insert_with(String::new) += description;
     }
 
-    let mut message_builder = CreateMessage::new().embed(embed_builder);
+    let SubmissionMessage { embeds, file } = submission_message(form, &record, &guild_locale);
+    let mut message_builder = CreateMessage::new()
+        .embeds(embeds)
+        .components(submission_components(&record, &guild_locale));
+
+    if let Some(file) = file {
+        message_builder = message_builder.add_file(file);
+    }
 
     if let Some(content) = content {
         message_builder = message_builder.content(content.trim_end());
     }
 
-    thread.send_message(ctx, message_builder).await?;
-    thread.id.add_thread_member(ctx, response.interaction.user.id).await?;
+    record.message_id = match thread.send_message(http, message_builder).await {
+        Ok(message) => message.id,
+        Err(e) => {
+            // Retrying creates a new thread, so this one would stay empty
+            if let Err(e) = thread.delete(http).await {
+                tracing::warn!(error = ?e, id = %submission.id, "Failed to delete thread of submission which could not be posted");
+            }
+            return Err(e.into());
+        }
+    };
+    state.metrics.submissions_created.inc();
+
+    // The submission is posted now, so nothing below may cause it to be retried and posted twice
+    if let Err(e) = finish_posting(http, state, &record, &thread).await {
+        tracing::warn!(error = ?e, id = %submission.id, "Failed to finish posting submission");
+    }
+
+    Ok(thread)
+}
+
+/// Records a posted submission and lets the submitter into its thread, or locks it if it needs no attention.
+async fn finish_posting(http: &Http, state: &State, record: &SubmissionRecord, thread: &GuildChannel) -> Result<(), Error> {
+    let submission = &record.submission;
+    state.save_submission(record).await?;
+    if record.closed {
+        thread.id.edit_thread(http, EditThread::new().locked(true).archived(true)).await?;
+    } else {
+        if submission.in_thread() {
+            thread.id.add_thread_member(http, submission.user_id).await?;
+        }
+        if submission.relay {
+            state.set_relay(submission).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Tells the submitter of a quiz which was decided automatically whether they passed.
+fn quiz_decision(language: &str, result: QuizResult) -> String {
+    let key = if result.passed == Some(true) { "quiz-passed" } else { "quiz-failed" };
+    tr_args(language, key, &[("points", &result.points), ("max", &result.max)])
+}
+
+/// Posts the answers of a modal; if that fails, they are saved so posting can be retried later.
+pub async fn create_response(ctx: &Context, state: &State, form: &Form, interaction: &ModalInteraction, answers: Vec<String>, flagged: bool) -> Result<(), Error> {
+    interaction.defer_ephemeral(ctx).await?;
+
+    let member = interaction.member.as_ref().expect("can only be run in guild");
+    let auto_decide = form.quiz.is_some_and(|q| q.auto_decide);
+    let quiz = form.grade(&answers).map(|result| QuizResult { decided: auto_decide && result.passed.is_some(), ..result });
+    let submission = Submission {
+        id: SubmissionId::new(),
+        guild_id: member.guild_id,
+        form_id: form.id(),
+        user_id: member.user.id,
+        user_name: member.display_name().to_owned(),
+        avatar_url: member.face(),
+        locale: interaction.locale.clone(),
+        guild_locale: interaction.guild_locale.clone(),
+        answers,
+        submitted_at: Timestamp::now(),
+        revision: form.revision(),
+        anonymous: form.anonymous,
+        relay: form.relay,
+        number: Some(state.next_ticket_number(FormRef::new(member.guild_id, form.id())).await?),
+        quiz,
+        flagged,
+    };
+
+    let settings = state.get_guild_settings(member.guild_id).await?;
+    let user_language = resolve([Some(interaction.locale.as_str()), settings.language.as_deref(), interaction.guild_locale.as_deref()]);
+
+    // The role is given right away, even if posting the submission has to be retried
+    let mut role_failed = false;
+    if let (Some(QuizResult { passed: Some(true), decided: true, .. }), Some(role)) = (quiz, form.quiz.and_then(|q| q.pass_role)) {
+        if let Err(e) = member.add_role(ctx, role).await {
+            tracing::warn!(error = ?e, %role, "Failed to give pass role of quiz");
+            role_failed = true;
+        }
+    }
+
+    let mut message = match post_submission(&ctx.http, state, form, &submission).await {
+        Ok(thread) => match quiz.filter(|q| q.decided) {
+            Some(result) => quiz_decision(user_language, result),
+            // The submitter cannot see the thread, so there is no point in linking it
+            None if submission.relay => tr(user_language, "relay-submission-sent"),
+            None if submission.anonymous => tr(user_language, "anonymous-submission-sent"),
+            None => tr_args(user_language, "thread-created", &[("thread", &thread)]),
+        },
+        Err(e) => {
+            tracing::warn!(error = ?e, "Failed to post submission; it will be retried");
+            state.metrics.submissions_failed.inc();
+            state.save_failed_submission(&FailedSubmission { submission, attempts: 1, error: e.to_string() }).await?;
+            tr(user_language, "submission-queued")
+        }
+    };
+    if role_failed {
+        message += "\n";
+        message += &tr(user_language, "quiz-role-failed");
+    }
 
-    response.interaction.edit_response(ctx, EditInteractionResponse::new().content(format!("{thread} has been created"))).await?;
+    interaction.edit_response(ctx, EditInteractionResponse::new().content(message)).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Tries to post a failed submission again. Returns the thread it was posted to, or why it failed.
+pub async fn retry_submission(http: &Http, state: &State, mut failed: FailedSubmission) -> Result<Result<GuildChannel, String>, Error> {
+    let submission = &failed.submission;
+
+    // Claim the submission so it is not posted twice if it is also being replayed by hand
+    if !state.claim_failed_submission(submission.guild_id, submission.id).await? {
+        return Ok(Err("the submission is already being posted".to_owned()));
+    }
+
+    let _in_flight = state.shutdown.track();
+    // It may have been posted or discarded since it was fetched
+    let result = if state.get_failed_submission(submission.guild_id, submission.id).await?.is_none() {
+        Err("the submission is no longer queued".to_owned())
+    } else {
+        let result = match state.get_form(submission.form_ref()).await? {
+            Some(form) => post_submission(http, state, &form, submission).await.map_err(|e| e.to_string()),
+            None => Err("the form no longer exists".to_owned()),
+        };
+
+        // Only removed once posted, so it is not lost if the bot stops in between
+        match &result {
+            Ok(_) => {
+                state.delete_failed_submission(submission.guild_id, submission.id).await?;
+            }
+            Err(error) => {
+                failed.attempts += 1;
+                failed.error.clone_from(error);
+                state.save_failed_submission(&failed).await?;
+            }
+        }
+        result
+    };
+
+    state.release_failed_submission(submission.guild_id, submission.id).await?;
+    Ok(result)
+}
+
+/// Periodically retries posting failed submissions until the bot shuts down.
+pub async fn retry_failed_submissions(http: Arc<Http>, state: State) {
+    let mut interval = tokio::time::interval(RETRY_INTERVAL);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let failed = match state.get_all_failed_submissions().await {
+            Ok(failed) => failed,
+            Err(e) => {
+                tracing::error!(error = ?e, "Failed to fetch failed submissions");
+                continue;
+            }
+        };
+
+        for failed in failed {
+            if state.shutdown.is_shutting_down() {
+                return;
+            }
+
+            let id = failed.submission.id;
+            match retry_submission(&http, &state, failed).await {
+                Ok(Ok(_)) => tracing::info!(%id, "Posted failed submission"),
+                Ok(Err(error)) => tracing::warn!(%id, error, "Failed to post failed submission again"),
+                Err(e) => tracing::error!(%id, error = ?e, "Error while retrying failed submission"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EMBED_FIELD_MAX_LENGTH, place_answers, Placement, truncate_answer, truncate_answers};
+
+    #[test]
+    fn truncates_long_answers() {
+        assert_eq!(truncate_answer("", 400), "-");
+        assert_eq!(truncate_answer("short", 400), "short");
+
+        let long = "æ".repeat(401);
+        assert_eq!(truncate_answer(&long, 400), "æ".repeat(400) + "…");
+        assert_eq!(truncate_answer(&long[..800], 400), "æ".repeat(400));
+        assert_eq!(truncate_answer(&long, EMBED_FIELD_MAX_LENGTH), long);
+    }
+
+    #[test]
+    fn answers_fit_in_one_embed() {
+        let long = "a".repeat(2000);
+        let answers = vec![("1".to_owned(), long.as_str()); 5];
+        let truncated = truncate_answers(&answers, 2000);
+        assert!(truncated.iter().all(|a| a.chars().count() <= EMBED_FIELD_MAX_LENGTH));
+        assert_eq!(truncated.iter().map(|a| a.chars().count()).sum::<usize>(), 6000 - 2000 - 5);
+        assert_eq!(truncated[3], "a".repeat(921) + "…");
+        assert_eq!(truncated[4], "…");
+
+        let answers = [("1".to_owned(), ""), ("2".to_owned(), "short")];
+        assert_eq!(truncate_answers(&answers, 6000), vec!["-", "…"]);
+    }
+
+    #[test]
+    fn answers_are_placed_within_limits() {
+        use Placement::*;
+
+        assert_eq!(place_answers(&[(10, 1024), (10, 0)], 100, 20), vec![Field, Field]);
+        assert_eq!(place_answers(&[(10, 1025), (10, 4000)], 100, 20), vec![Embed, Embed]);
+        // 4000 + 4000 characters do not fit in one message, so the second answer goes in the file
+        assert_eq!(place_answers(&[(10, 4000), (10, 4000), (10, 500)], 100, 20), vec![Embed, File, Field]);
+        assert_eq!(place_answers(&[(10, 10); 5], 6000, 20), vec![Field; 5]);
+        assert_eq!(place_answers(&[(10, 100); 5], 6000, 20), vec![File; 5]);
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+/// Tracks whether the bot is shutting down and how many submissions are still being posted.
+#[derive(Default)]
+pub struct Shutdown {
+    shutting_down: AtomicBool,
+    in_flight: AtomicUsize,
+    drained: Notify,
+}
+
+/// Marks a submission as in-flight until dropped.
+pub struct InFlight(Arc<Shutdown>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.drained.notify_waiters();
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Marks a submission as in-flight. This is allowed while shutting down, so that forms which
+    /// were already open are still posted during the grace period.
+    pub fn track(self: &Arc<Self>) -> InFlight {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlight(self.clone())
+    }
+
+    /// Stops new interactions and waits until no submissions are in-flight or the timeout passes.
+    /// Returns whether all submissions finished in time.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        tokio::time::timeout(timeout, async {
+            loop {
+                let drained = self.drained.notified();
+                tokio::pin!(drained);
+                drained.as_mut().enable();
+
+                if self.in_flight.load(Ordering::SeqCst) == 0 {
+                    return;
+                }
+
+                drained.await;
+            }
+        }).await.is_ok()
+    }
+}
+
+#[cfg(unix)]
+pub async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn wait_for_signal() {
+    tokio::signal::ctrl_c().await.expect("failed to listen for Ctrl+C");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::Shutdown;
+
+    #[tokio::test]
+    async fn drain_waits_for_in_flight() {
+        let shutdown = Arc::new(Shutdown::default());
+        let in_flight = shutdown.track();
+
+        let drain = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.drain(Duration::from_secs(5)).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(shutdown.is_shutting_down());
+        drop(in_flight);
+        assert!(drain.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn drain_times_out() {
+        let shutdown = Arc::new(Shutdown::default());
+        let _in_flight = shutdown.track();
+        assert!(!shutdown.drain(Duration::from_millis(10)).await);
+    }
+}
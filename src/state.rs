@@ -1,18 +1,39 @@
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
 use poise::serenity_prelude::*;
 use poise::SlashArgError;
-use redis::{AsyncCommands, FromRedisValue, RedisResult, RedisWrite, SetExpiry, SetOptions, ToRedisArgs, Value};
+use redis::{AsyncCommands, ExistenceCheck, FromRedisValue, RedisResult, RedisWrite, SetExpiry, SetOptions, ToRedisArgs, Value};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::metrics::{Metrics, TimedConnection};
+use crate::modals::{ModalId, ModalKind};
+use crate::shutdown::Shutdown;
+
 pub const LABEL_MAX_LENGTH: usize = 45;
 pub const PLACEHOLDER_MAX_LENGTH: usize = 100;
+/// The longest answer to a short field; it always fits in an embed field.
 pub const FIELD_RESPONSE_MAX_LENGTH: u16 = 1024;
-
-#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+/// The longest answer to a paragraph field, which is as much as Discord allows in a modal.
+pub const PARAGRAPH_RESPONSE_MAX_LENGTH: u16 = 4000;
+pub const EMBED_DESCRIPTION_MAX_LENGTH: usize = 4096;
+/// The most inputs Discord allows in one modal.
+pub const PAGE_MAX_FIELDS: usize = 5;
+/// How long the answers to the first pages of a form are kept while the user fills in the rest.
+const DRAFT_EXPIRY: Duration = Duration::from_secs(60 * 60);
+/// How long a failed submission stays claimed if the bot stops while posting it.
+const RETRY_CLAIM_EXPIRY: Duration = Duration::from_secs(10 * 60);
+/// How long after reporting blocked terms from a user no more of theirs are reported.
+const FILTER_REPORT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct FormId(Uuid);
 
 impl FromStr for FormId {
@@ -45,6 +66,80 @@ impl poise::SlashArgument for FormId {
     }
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PanelId(Uuid);
+
+impl FromStr for PanelId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Uuid::try_parse(s).map(PanelId)
+    }
+}
+
+impl Display for PanelId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Uuid::fmt(&self.0, f)
+    }
+}
+
+#[async_trait]
+impl poise::SlashArgument for PanelId {
+    async fn extract(_ctx: &Context, _interaction: &CommandInteraction, value: &ResolvedValue<'_>) -> std::result::Result<Self, SlashArgError> {
+        let value = match value {
+            ResolvedValue::String(str) => str,
+            _ => return Err(SlashArgError::new_command_structure_mismatch("expected string")),
+        };
+        value.parse().map_err(|_| SlashArgError::new_command_structure_mismatch("expected uuid"))
+    }
+
+    fn create(builder: CreateCommandOption) -> CreateCommandOption {
+        builder.kind(CommandOptionType::String)
+    }
+}
+
+/// Identifies a field regardless of its position in the form.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct FieldId(Uuid);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SubmissionId(Uuid);
+
+impl SubmissionId {
+    pub fn new() -> Self {
+        SubmissionId(Uuid::new_v4())
+    }
+}
+
+impl FromStr for SubmissionId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Uuid::try_parse(s).map(SubmissionId)
+    }
+}
+
+impl Display for SubmissionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Uuid::fmt(&self.0, f)
+    }
+}
+
+#[async_trait]
+impl poise::SlashArgument for SubmissionId {
+    async fn extract(_ctx: &Context, _interaction: &CommandInteraction, value: &ResolvedValue<'_>) -> std::result::Result<Self, SlashArgError> {
+        let value = match value {
+            ResolvedValue::String(str) => str,
+            _ => return Err(SlashArgError::new_command_structure_mismatch("expected string")),
+        };
+        value.parse().map_err(|_| SlashArgError::new_command_structure_mismatch("expected uuid"))
+    }
+
+    fn create(builder: CreateCommandOption) -> CreateCommandOption {
+        builder.kind(CommandOptionType::String)
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct FormRef {
     pub guild_id: GuildId,
@@ -75,6 +170,8 @@ impl poise::SlashArgument for FormRef {
 #[derive(Clone)]
 pub struct State {
     pub connection_manager: redis::aio::ConnectionManager,
+    pub metrics: Arc<Metrics>,
+    pub shutdown: Arc<Shutdown>,
 }
 
 fn get_forms_key(guild_id: GuildId) -> String {
@@ -85,21 +182,164 @@ fn get_cooldown_key(FormRef { guild_id, form_id }: FormRef, user_id: UserId) ->
     format!("forms:{guild_id}:{form_id}:{user_id}")
 }
 
+fn get_cooldown_key_pattern(FormRef { guild_id, form_id }: FormRef) -> String {
+    format!("forms:{guild_id}:{form_id}:*")
+}
+
+fn get_quota_key(FormRef { guild_id, form_id }: FormRef, user_id: UserId) -> String {
+    format!("quota:{guild_id}:{form_id}:{user_id}")
+}
+
+/// Checks the cooldown and quota of a user and, unless this is a dry run, atomically starts the
+/// cooldown and counts the submission towards the quota. Returns the rejection kind (0 if allowed)
+/// along with the milliseconds until the user may submit again.
+static SUBMISSION_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| redis::Script::new(r"
+    local cooldown_until, now = tonumber(ARGV[1]), tonumber(ARGV[2])
+    local window, limit = tonumber(ARGV[3]), tonumber(ARGV[4])
+    local dry_run = ARGV[6] == '1'
+
+    -- Cooldowns set by staff apply even if the form has no cooldown of its own
+    local ttl = redis.call('PTTL', KEYS[1])
+    if ttl > 0 then
+        return {1, ttl}
+    end
+
+    if window > 0 then
+        redis.call('ZREMRANGEBYSCORE', KEYS[2], '-inf', now - window)
+        local used = redis.call('ZCARD', KEYS[2])
+        if used >= limit then
+            local freed = redis.call('ZRANGE', KEYS[2], used - limit, used - limit, 'WITHSCORES')
+            return {2, tonumber(freed[2]) + window - now}
+        end
+    end
+
+    if dry_run then
+        return {0, 0}
+    end
+
+    if cooldown_until > 0 then
+        redis.call('SET', KEYS[1], 1, 'PXAT', cooldown_until)
+    end
+    if window > 0 then
+        redis.call('ZADD', KEYS[2], now, ARGV[5])
+        redis.call('PEXPIRE', KEYS[2], window)
+    end
+    return {0, 0}
+"));
+
+/// Why a user may not submit a form right now, along with how long until they can.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    Cooldown(Duration),
+    Quota(Duration),
+}
+
+/// A submission counted towards the cooldown and quota of a form before it was posted.
+pub struct Reservation {
+    form_ref: FormRef,
+    user_id: UserId,
+    cooldown: bool,
+    quota_entry: Option<String>,
+}
+
+fn get_answers_key(FormRef { guild_id, form_id }: FormRef, user_id: UserId) -> String {
+    format!("answers:{guild_id}:{form_id}:{user_id}")
+}
+
+fn get_draft_key(FormRef { guild_id, form_id }: FormRef, user_id: UserId) -> String {
+    format!("drafts:{guild_id}:{form_id}:{user_id}")
+}
+
+fn get_submissions_key(guild_id: GuildId) -> String {
+    format!("submissions:{guild_id}")
+}
+
+fn get_thread_submissions_key(guild_id: GuildId) -> String {
+    format!("thread-submissions:{guild_id}")
+}
+
+fn get_form_submissions_key(FormRef { guild_id, form_id }: FormRef) -> String {
+    format!("form-submissions:{guild_id}:{form_id}")
+}
+
+fn get_ticket_submissions_key(FormRef { guild_id, form_id }: FormRef) -> String {
+    format!("ticket-submissions:{guild_id}:{form_id}")
+}
+
+fn get_tickets_key(FormRef { guild_id, form_id }: FormRef) -> String {
+    format!("tickets:{guild_id}:{form_id}")
+}
+
+fn get_counters_key(FormRef { guild_id, form_id }: FormRef) -> String {
+    format!("stats:{guild_id}:{form_id}")
+}
+
+fn get_relay_key(user_id: UserId) -> String {
+    format!("relay:{user_id}")
+}
+
+fn get_failed_key(guild_id: GuildId) -> String {
+    format!("failed:{guild_id}")
+}
+
+fn get_retry_claim_key(guild_id: GuildId, id: SubmissionId) -> String {
+    format!("retrying:{guild_id}:{id}")
+}
+
+fn get_filter_report_key(channel_id: ChannelId, user_id: UserId) -> String {
+    format!("filter-reported:{channel_id}:{user_id}")
+}
+
+fn get_failed_key_pattern() -> String {
+    "failed:*".to_owned()
+}
+
+fn get_settings_key(guild_id: GuildId) -> String {
+    format!("settings:{guild_id}")
+}
+
+fn get_panels_key(guild_id: GuildId) -> String {
+    format!("panels:{guild_id}")
+}
+
+fn get_launchers_key(FormRef { guild_id, form_id }: FormRef) -> String {
+    format!("launchers:{guild_id}:{form_id}")
+}
+
 impl State {
+    fn redis(&self) -> TimedConnection {
+        TimedConnection::new(self.connection_manager.clone(), self.metrics.clone())
+    }
+
+    pub async fn ping(&self) -> Result<(), crate::Error> {
+        let _: () = redis::cmd("PING").query_async(&mut self.redis()).await?;
+        Ok(())
+    }
+
+    /// Asks Redis to close the connection; it is only reopened if another request is made.
+    pub async fn close(&self) -> Result<(), crate::Error> {
+        let _: () = redis::cmd("QUIT").query_async(&mut self.connection_manager.clone()).await?;
+        Ok(())
+    }
+
     pub async fn get_form(&self, form_ref: FormRef) -> Result<Option<Form>, crate::Error> {
-        Ok(self.connection_manager.clone().hget(get_forms_key(form_ref.guild_id), form_ref.form_id.to_string()).await?)
+        Ok(self.redis().hget(get_forms_key(form_ref.guild_id), form_ref.form_id.to_string()).await?)
     }
 
     pub async fn save_form(&self, guild_id: GuildId, new_form: &Form) -> Result<(), crate::Error> {
-        Ok(self.connection_manager.clone().hset(get_forms_key(guild_id), new_form.id.to_string(), new_form).await?)
+        Ok(self.redis().hset(get_forms_key(guild_id), new_form.id.to_string(), new_form).await?)
     }
 
     pub async fn delete_form(&self, guild_id: GuildId, id: FormId) -> Result<bool, crate::Error> {
-        Ok(self.connection_manager.clone().hdel(get_forms_key(guild_id), id.to_string()).await?)
+        Ok(self.redis().hdel(get_forms_key(guild_id), id.to_string()).await?)
+    }
+
+    pub async fn get_forms(&self, guild_id: GuildId) -> Result<Vec<Form>, crate::Error> {
+        Ok(self.redis().hvals(get_forms_key(guild_id)).await?)
     }
 
     pub async fn get_form_ids(&self, guild_id: GuildId) -> Result<Vec<(FormId, String)>, crate::Error> {
-        let forms: Vec<Form> = self.connection_manager.clone().hvals(get_forms_key(guild_id)).await?;
+        let forms = self.get_forms(guild_id).await?;
         Ok(forms.into_iter().map(|f| (f.id, f.title.clone())).collect())
     }
 
@@ -107,201 +347,1198 @@ impl State {
         Ok(self.get_form(form_ref).await?.map(|f| f.fields))
     }
 
-    pub async fn cooldown(&self, form_ref: FormRef, user_id: UserId) -> Result<Option<Duration>, crate::Error> {
-        let ttl: i64 = self.connection_manager.clone().ttl(get_cooldown_key(form_ref, user_id)).await?;
-        Ok(match ttl {
-            ..=0 => None,
-            s => Some(Duration::from_secs(s as u64))
+    async fn run_submission_script(&self, form_ref: FormRef, form: &Form, user_id: UserId, entry: &str, dry_run: bool) -> Result<Option<Rejection>, crate::Error> {
+        let now = Utc::now();
+        let cooldown_until = form.cooldown_expiry(now).map_or(0, |t| t.timestamp_millis());
+        let (window, limit) = form.quota.map_or((0, 0), |q| (q.window.as_millis() as u64, q.count));
+
+        let (kind, wait): (u8, u64) = SUBMISSION_SCRIPT
+            .key(get_cooldown_key(form_ref, user_id))
+            .key(get_quota_key(form_ref, user_id))
+            .arg(cooldown_until)
+            .arg(now.timestamp_millis())
+            .arg(window)
+            .arg(limit)
+            .arg(entry)
+            .arg(dry_run)
+            .invoke_async(&mut self.redis()).await?;
+
+        Ok(match kind {
+            1 => Some(Rejection::Cooldown(Duration::from_millis(wait))),
+            2 => Some(Rejection::Quota(Duration::from_millis(wait))),
+            _ => None,
         })
     }
 
-    pub async fn trigger_cooldown(&self, guild_id: GuildId, form: &Form, user_id: UserId) -> Result<(), crate::Error> {
-        let Some(duration) = form.cooldown else {
-            return Ok(())
-        };
+    /// Checks whether the cooldown or quota of a form prevents the user from submitting it.
+    pub async fn check_submission(&self, guild_id: GuildId, form: &Form, user_id: UserId) -> Result<Option<Rejection>, crate::Error> {
+        self.run_submission_script(FormRef::new(guild_id, form.id), form, user_id, "", true).await
+    }
 
-        self.connection_manager.clone().set_options(
-            get_cooldown_key(FormRef::new(guild_id, form.id), user_id), 1,
-            SetOptions::default().with_expiration(SetExpiry::EX(duration.as_secs())),
-        ).await?;
+    /// Atomically starts the cooldown and counts the submission towards the quota of the form, so
+    /// that a user with several modals open can only submit one of them.
+    pub async fn reserve_submission(&self, guild_id: GuildId, form: &Form, user_id: UserId) -> Result<Result<Reservation, Rejection>, crate::Error> {
+        let form_ref = FormRef::new(guild_id, form.id);
+        let entry = Uuid::new_v4().to_string();
+        if let Some(rejection) = self.run_submission_script(form_ref, form, user_id, &entry, false).await? {
+            return Ok(Err(rejection));
+        }
+
+        Ok(Ok(Reservation {
+            form_ref,
+            user_id,
+            cooldown: form.cooldown_expiry(Utc::now()).is_some(),
+            quota_entry: form.quota.map(|_| entry),
+        }))
+    }
+
+    /// Undoes a reservation, e.g. because the submission could not be posted.
+    pub async fn release_submission(&self, reservation: Reservation) -> Result<(), crate::Error> {
+        if !reservation.cooldown && reservation.quota_entry.is_none() {
+            return Ok(());
+        }
+
+        let mut pipe = redis::pipe();
+        if reservation.cooldown {
+            pipe.del(get_cooldown_key(reservation.form_ref, reservation.user_id));
+        }
+        if let Some(entry) = reservation.quota_entry {
+            pipe.zrem(get_quota_key(reservation.form_ref, reservation.user_id), entry);
+        }
+        pipe.query_async::<()>(&mut self.redis()).await?;
         Ok(())
     }
 
     pub async fn clear_cooldown(&self, form_ref: FormRef, user_id: UserId) -> Result<bool, crate::Error> {
-        Ok(self.connection_manager.clone().del(get_cooldown_key(form_ref, user_id)).await?)
+        Ok(self.redis().del(get_cooldown_key(form_ref, user_id)).await?)
     }
-}
 
-#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
-pub enum SerializableMention {
-    Role(RoleId),
-    User(UserId),
-}
+    pub async fn set_cooldown(&self, form_ref: FormRef, user_id: UserId, duration: Duration) -> Result<(), crate::Error> {
+        self.redis().set_options::<_, _, ()>(
+            get_cooldown_key(form_ref, user_id), 1,
+            SetOptions::default().with_expiration(SetExpiry::EX(duration.as_secs())),
+        ).await?;
+        Ok(())
+    }
 
-impl SerializableMention {
-    fn mention(&self) -> Mention {
-        match *self {
-            SerializableMention::Role(r) => Mention::Role(r),
-            SerializableMention::User(u) => Mention::User(u),
+    async fn scan_keys(&self, pattern: String) -> Result<Vec<String>, crate::Error> {
+        let mut connection = self.redis();
+        let mut iter = connection.scan_match::<_, String>(pattern).await?;
+        let mut keys = vec![];
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
         }
+        Ok(keys)
     }
-}
 
-impl Display for SerializableMention {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        std::fmt::Display::fmt(&self.mention(), f)
+    async fn delete_keys(&self, keys: Vec<String>) -> Result<usize, crate::Error> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        Ok(self.redis().del(keys).await?)
     }
-}
 
-#[async_trait]
-impl poise::SlashArgument for SerializableMention {
-    async fn extract(_ctx: &Context, _interaction: &CommandInteraction, value: &ResolvedValue<'_>) -> Result<Self, SlashArgError> {
-        match value {
-            ResolvedValue::Role(r) => Ok(SerializableMention::Role(r.id)),
-            ResolvedValue::User(u, _) => Ok(SerializableMention::User(u.id)),
-            _ => Err(SlashArgError::new_command_structure_mismatch("expected mentionable")),
+    /// Lists the users on cooldown for a form along with their remaining cooldown.
+    pub async fn list_cooldowns(&self, form_ref: FormRef) -> Result<Vec<(UserId, Duration)>, crate::Error> {
+        let keys = self.scan_keys(get_cooldown_key_pattern(form_ref)).await?;
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.ttl(key);
         }
+        let ttls: Vec<i64> = pipe.query_async(&mut self.redis()).await?;
+
+        let mut cooldowns = keys.iter().zip(ttls)
+            .filter(|(_, ttl)| *ttl > 0)
+            .filter_map(|(key, ttl)| {
+                let user_id = key.rsplit(':').next()?.parse().ok()?;
+                Some((UserId::new(user_id), Duration::from_secs(ttl as u64)))
+            })
+            .collect::<Vec<_>>();
+        cooldowns.sort_by_key(|(_, remaining)| *remaining);
+        Ok(cooldowns)
     }
 
-    fn create(builder: CreateCommandOption) -> CreateCommandOption {
-        builder.kind(CommandOptionType::Mentionable)
+    /// Clears the cooldowns of all users for a form.
+    pub async fn clear_cooldowns(&self, form_ref: FormRef) -> Result<usize, crate::Error> {
+        let keys = self.scan_keys(get_cooldown_key_pattern(form_ref)).await?;
+        self.delete_keys(keys).await
     }
-}
 
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
-pub struct FormField {
-    name: String,
-    pub style: InputTextStyle,
-    placeholder: Option<String>,
-    pub min_length: Option<u16>,
-    pub max_length: Option<u16>,
-    pub required: bool,
-    pub inline: bool,
-}
+    /// Clears the cooldowns of a user for every form in a guild.
+    pub async fn clear_user_cooldowns(&self, guild_id: GuildId, user_id: UserId) -> Result<usize, crate::Error> {
+        let keys = self.scan_keys(format!("forms:{guild_id}:*:{user_id}")).await?;
+        self.delete_keys(keys).await
+    }
 
-impl FormField {
-    pub fn new(name: String, style: InputTextStyle) -> Result<Self, ValueTooLong> {
-        Self::validate_name(&name)?;
+    pub async fn get_launchers(&self, form_ref: FormRef) -> Result<Vec<Launcher>, crate::Error> {
+        Ok(self.redis().hvals(get_launchers_key(form_ref)).await?)
+    }
 
-        Ok(Self {
-            name,
-            style,
-            placeholder: None,
-            min_length: None,
-            max_length: None,
-            required: true,
-            inline: false,
-        })
+    pub async fn get_launcher(&self, form_ref: FormRef, message_id: MessageId) -> Result<Option<Launcher>, crate::Error> {
+        Ok(self.redis().hget(get_launchers_key(form_ref), message_id.to_string()).await?)
     }
 
-    pub fn name(&self) -> &str {
-        &self.name
+    pub async fn save_launcher(&self, form_ref: FormRef, launcher: &Launcher) -> Result<(), crate::Error> {
+        Ok(self.redis().hset(get_launchers_key(form_ref), launcher.message_id.to_string(), launcher).await?)
     }
 
-    pub fn set_name(&mut self, name: String) -> Result<(), ValueTooLong> {
-        Self::validate_name(&name)?;
+    pub async fn delete_launcher(&self, form_ref: FormRef, message_id: MessageId) -> Result<bool, crate::Error> {
+        Ok(self.redis().hdel(get_launchers_key(form_ref), message_id.to_string()).await?)
+    }
 
-        self.name = name;
-        Ok(())
+    pub async fn get_panel(&self, guild_id: GuildId, id: PanelId) -> Result<Option<Panel>, crate::Error> {
+        Ok(self.redis().hget(get_panels_key(guild_id), id.to_string()).await?)
     }
 
-    pub fn placeholder(&self) -> Option<&str> {
-        self.placeholder.as_deref()
+    pub async fn get_panels(&self, guild_id: GuildId) -> Result<Vec<Panel>, crate::Error> {
+        Ok(self.redis().hvals(get_panels_key(guild_id)).await?)
     }
 
-    pub fn set_placeholder(&mut self, placeholder: Option<String>) -> Result<(), ValueTooLong> {
-        if placeholder.as_ref().map(|p| p.len() > PLACEHOLDER_MAX_LENGTH).unwrap_or(false) {
-            return Err(ValueTooLong);
-        }
+    pub async fn save_panel(&self, guild_id: GuildId, panel: &Panel) -> Result<(), crate::Error> {
+        Ok(self.redis().hset(get_panels_key(guild_id), panel.id.to_string(), panel).await?)
+    }
 
-        self.placeholder = placeholder;
-        Ok(())
+    pub async fn delete_panel(&self, guild_id: GuildId, id: PanelId) -> Result<bool, crate::Error> {
+        Ok(self.redis().hdel(get_panels_key(guild_id), id.to_string()).await?)
     }
 
-    fn input_text<T: Into<String>>(&self, custom_id: T) -> CreateInputText {
-        let mut builder = CreateInputText::new(self.style, &self.name, custom_id)
-            .max_length(self.min_length.unwrap_or(FIELD_RESPONSE_MAX_LENGTH))
-            .required(self.required);
+    pub async fn get_submission(&self, guild_id: GuildId, id: SubmissionId) -> Result<Option<SubmissionRecord>, crate::Error> {
+        Ok(self.redis().hget(get_submissions_key(guild_id), id.to_string()).await?)
+    }
 
-        if let Some(placeholder) = &self.placeholder {
-            builder = builder.placeholder(placeholder);
-        }
+    /// Lists the posted submissions of a guild, oldest first.
+    pub async fn get_submissions(&self, guild_id: GuildId) -> Result<Vec<SubmissionRecord>, crate::Error> {
+        let mut records: Vec<SubmissionRecord> = self.redis().hvals(get_submissions_key(guild_id)).await?;
+        records.sort_by_key(|r| r.submission.submitted_at);
+        Ok(records)
+    }
 
-        if let Some(min_length) = self.min_length {
-            builder = builder.min_length(min_length);
+    /// Lists the posted submissions to a form, oldest first.
+    pub async fn get_form_submissions(&self, form_ref: FormRef) -> Result<Vec<SubmissionRecord>, crate::Error> {
+        let ids: Vec<String> = self.redis().smembers(get_form_submissions_key(form_ref)).await?;
+        if ids.is_empty() {
+            return Ok(vec![]);
         }
 
-        builder
+        let records: Vec<Option<SubmissionRecord>> = redis::cmd("HMGET").arg(get_submissions_key(form_ref.guild_id)).arg(ids).query_async(&mut self.redis()).await?;
+        let mut records = records.into_iter().flatten().collect::<Vec<_>>();
+        records.sort_by_key(|r| r.submission.submitted_at);
+        Ok(records)
     }
 
-    pub fn apply_to_embed(&self, embed: CreateEmbed, value: String) -> CreateEmbed {
-        embed.field(&self.name, value, self.inline)
+    pub async fn get_submission_by_thread(&self, guild_id: GuildId, thread_id: ChannelId) -> Result<Option<SubmissionRecord>, crate::Error> {
+        let id: Option<String> = self.redis().hget(get_thread_submissions_key(guild_id), thread_id.to_string()).await?;
+        match id.and_then(|id| id.parse().ok()) {
+            Some(id) => self.get_submission(guild_id, id).await,
+            None => Ok(None),
+        }
     }
 
-    fn validate_name(name: &str) -> Result<(), ValueTooLong> {
-        if name.len() > LABEL_MAX_LENGTH {
-            Err(ValueTooLong)
-        } else {
-            Ok(())
+    pub async fn get_submission_by_ticket(&self, form_ref: FormRef, number: u64) -> Result<Option<SubmissionRecord>, crate::Error> {
+        let id: Option<String> = self.redis().hget(get_ticket_submissions_key(form_ref), number).await?;
+        match id.and_then(|id| id.parse().ok()) {
+            Some(id) => self.get_submission(form_ref.guild_id, id).await,
+            None => Ok(None),
         }
     }
-}
-
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
-pub struct Form {
-    id: FormId,
-    title: String,
-    description: Option<String>,
-    fields: Vec<FormField>,
-    pub destination: ChannelId,
-    pub mention: Option<SerializableMention>,
-    cooldown: Option<Duration>,
-}
 
-impl FromRedisValue for Form {
-    fn from_redis_value(v: &Value) -> RedisResult<Self> {
-        let serialized = <String as FromRedisValue>::from_redis_value(v)?;
-        serde_json::from_str(&serialized).map_err(|e| (redis::ErrorKind::ParseError, "not valid form json", e.to_string()).into())
+    /// Gets the submission whose thread a user's direct messages are relayed to.
+    pub async fn get_relay(&self, user_id: UserId) -> Result<Option<(GuildId, SubmissionId)>, crate::Error> {
+        let relay: Option<String> = self.redis().get(get_relay_key(user_id)).await?;
+        Ok(relay.and_then(|r| {
+            let (guild_id, id) = r.split_once(':')?;
+            Some((guild_id.parse().ok()?, id.parse().ok()?))
+        }))
     }
-}
 
-impl ToRedisArgs for Form {
-    fn write_redis_args<W>(&self, out: &mut W)
-    where
-        W: ?Sized + RedisWrite,
-    {
-        let serialized = serde_json::to_vec(self).expect("failed to serialize form json");
-        out.write_arg(&serialized);
+    /// Relays the direct messages of the submitter to the thread of this submission from now on.
+    pub async fn set_relay(&self, submission: &Submission) -> Result<(), crate::Error> {
+        let relay = format!("{}:{}", submission.guild_id, submission.id);
+        Ok(self.redis().set(get_relay_key(submission.user_id), relay).await?)
     }
-}
-
-#[derive(Debug, Eq, PartialEq)]
-pub enum AddFieldError {
-    TooManyFields,
-    IllegalAddBefore,
-}
 
-impl Display for AddFieldError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            AddFieldError::TooManyFields => write!(f, "too many fields"),
-            AddFieldError::IllegalAddBefore => write!(f, "illegal add-before target"),
+    /// Saves a submission, indexing it by its form, thread and ticket number so it can be found by them.
+    pub async fn save_submission(&self, record: &SubmissionRecord) -> Result<(), crate::Error> {
+        let submission = &record.submission;
+        let id = submission.id.to_string();
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .hset(get_submissions_key(submission.guild_id), &id, record)
+            .hset(get_thread_submissions_key(submission.guild_id), record.thread_id.to_string(), &id)
+            .sadd(get_form_submissions_key(submission.form_ref()), &id);
+        if let Some(number) = submission.number {
+            pipe.hset(get_ticket_submissions_key(submission.form_ref()), number, &id);
         }
+        pipe.query_async::<()>(&mut self.redis()).await?;
+        Ok(())
     }
-}
 
-impl std::error::Error for AddFieldError {}
+    /// Counts something happening to a form, for its statistics.
+    pub async fn count(&self, form_ref: FormRef, counter: FormCounter) -> Result<(), crate::Error> {
+        let _: () = self.redis().hincr(get_counters_key(form_ref), counter.field(), 1).await?;
+        Ok(())
+    }
 
-#[derive(Debug)]
-pub struct ValueTooLong;
+    pub async fn get_form_counters(&self, form_ref: FormRef) -> Result<FormCounters, crate::Error> {
+        let counters: HashMap<String, u64> = self.redis().hgetall(get_counters_key(form_ref)).await?;
+        let get = |counter: FormCounter| counters.get(counter.field()).copied().unwrap_or_default();
+        Ok(FormCounters {
+            opened: get(FormCounter::Opened),
+            submitted: get(FormCounter::Submitted),
+            cooldown_rejections: get(FormCounter::CooldownRejected),
+        })
+    }
 
-impl Display for ValueTooLong {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "field too long")
+    /// Hands out the next ticket number of a form, starting from 1.
+    pub async fn next_ticket_number(&self, form_ref: FormRef) -> Result<u64, crate::Error> {
+        Ok(self.redis().incr(get_tickets_key(form_ref), 1).await?)
     }
-}
 
-impl std::error::Error for ValueTooLong {}
+    async fn get_answers(&self, key: String) -> Result<HashMap<FieldId, String>, crate::Error> {
+        let answers: HashMap<String, String> = self.redis().hgetall(key).await?;
+        Ok(answers.into_iter().filter_map(|(id, answer)| Some((FieldId(id.parse().ok()?), answer))).collect())
+    }
+
+    /// Gets the answers of the latest submission of a user to a form, by field.
+    pub async fn get_last_answers(&self, form_ref: FormRef, user_id: UserId) -> Result<HashMap<FieldId, String>, crate::Error> {
+        self.get_answers(get_answers_key(form_ref, user_id)).await
+    }
+
+    /// Gets the answers a user has given to the pages of a form so far, by field.
+    pub async fn get_draft(&self, form_ref: FormRef, user_id: UserId) -> Result<HashMap<FieldId, String>, crate::Error> {
+        self.get_answers(get_draft_key(form_ref, user_id)).await
+    }
+
+    pub async fn save_draft(&self, form_ref: FormRef, user_id: UserId, answers: &HashMap<FieldId, String>) -> Result<(), crate::Error> {
+        let key = get_draft_key(form_ref, user_id);
+        let answers = answers.iter().map(|(id, answer)| (id.0.to_string(), answer)).collect::<Vec<_>>();
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(&key);
+        if !answers.is_empty() {
+            pipe.hset_multiple(&key, &answers).expire(&key, DRAFT_EXPIRY.as_secs() as i64);
+        }
+        pipe.query_async::<()>(&mut self.redis()).await?;
+        Ok(())
+    }
+
+    pub async fn delete_draft(&self, form_ref: FormRef, user_id: UserId) -> Result<(), crate::Error> {
+        Ok(self.redis().del(get_draft_key(form_ref, user_id)).await?)
+    }
+
+    pub async fn save_last_answers(&self, form_ref: FormRef, user_id: UserId, answers: &HashMap<FieldId, String>) -> Result<(), crate::Error> {
+        let key = get_answers_key(form_ref, user_id);
+        let answers = answers.iter().map(|(id, answer)| (id.0.to_string(), answer)).collect::<Vec<_>>();
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(&key);
+        if !answers.is_empty() {
+            pipe.hset_multiple(&key, &answers);
+        }
+        pipe.query_async::<()>(&mut self.redis()).await?;
+        Ok(())
+    }
+
+    pub async fn get_failed_submission(&self, guild_id: GuildId, id: SubmissionId) -> Result<Option<FailedSubmission>, crate::Error> {
+        Ok(self.redis().hget(get_failed_key(guild_id), id.to_string()).await?)
+    }
+
+    pub async fn get_failed_submissions(&self, guild_id: GuildId) -> Result<Vec<FailedSubmission>, crate::Error> {
+        let mut failed: Vec<FailedSubmission> = self.redis().hvals(get_failed_key(guild_id)).await?;
+        failed.sort_by_key(|f| f.submission.submitted_at);
+        Ok(failed)
+    }
+
+    /// Lists the failed submissions of every guild.
+    pub async fn get_all_failed_submissions(&self) -> Result<Vec<FailedSubmission>, crate::Error> {
+        let mut failed = vec![];
+        for key in self.scan_keys(get_failed_key_pattern()).await? {
+            let submissions: Vec<FailedSubmission> = self.redis().hvals(key).await?;
+            failed.extend(submissions);
+        }
+        Ok(failed)
+    }
+
+    pub async fn save_failed_submission(&self, failed: &FailedSubmission) -> Result<(), crate::Error> {
+        let submission = &failed.submission;
+        Ok(self.redis().hset(get_failed_key(submission.guild_id), submission.id.to_string(), failed).await?)
+    }
+
+    pub async fn delete_failed_submission(&self, guild_id: GuildId, id: SubmissionId) -> Result<bool, crate::Error> {
+        Ok(self.redis().hdel(get_failed_key(guild_id), id.to_string()).await?)
+    }
+
+    /// Claims a failed submission while it is being posted again, so it is not posted twice if it is
+    /// also replayed by hand. Returns whether it was not already claimed.
+    pub async fn claim_failed_submission(&self, guild_id: GuildId, id: SubmissionId) -> Result<bool, crate::Error> {
+        let claimed: Value = self.redis().set_options(
+            get_retry_claim_key(guild_id, id), 1,
+            SetOptions::default().conditional_set(ExistenceCheck::NX).with_expiration(SetExpiry::EX(RETRY_CLAIM_EXPIRY.as_secs())),
+        ).await?;
+        Ok(claimed != Value::Nil)
+    }
+
+    pub async fn release_failed_submission(&self, guild_id: GuildId, id: SubmissionId) -> Result<(), crate::Error> {
+        let _: () = self.redis().del(get_retry_claim_key(guild_id, id)).await?;
+        Ok(())
+    }
+
+    /// Claims the filter log channel for reporting an incident of a user.
+    /// Returns whether none of theirs was reported there recently.
+    pub async fn claim_filter_report(&self, channel_id: ChannelId, user_id: UserId) -> Result<bool, crate::Error> {
+        let claimed: Value = self.redis().set_options(
+            get_filter_report_key(channel_id, user_id), 1,
+            SetOptions::default().conditional_set(ExistenceCheck::NX).with_expiration(SetExpiry::EX(FILTER_REPORT_INTERVAL.as_secs())),
+        ).await?;
+        Ok(claimed != Value::Nil)
+    }
+
+    pub async fn get_guild_settings(&self, guild_id: GuildId) -> Result<GuildSettings, crate::Error> {
+        let settings: Option<GuildSettings> = self.redis().get(get_settings_key(guild_id)).await?;
+        Ok(settings.unwrap_or_default())
+    }
+
+    pub async fn save_guild_settings(&self, guild_id: GuildId, settings: &GuildSettings) -> Result<(), crate::Error> {
+        Ok(self.redis().set(get_settings_key(guild_id), settings).await?)
+    }
+}
+
+fn from_json_value<T: serde::de::DeserializeOwned>(v: &Value, name: &'static str) -> RedisResult<T> {
+    let serialized = <String as FromRedisValue>::from_redis_value(v)?;
+    serde_json::from_str(&serialized).map_err(|e| (redis::ErrorKind::ParseError, name, e.to_string()).into())
+}
+
+fn write_json_arg<T: Serialize, W: ?Sized + RedisWrite>(value: &T, out: &mut W) {
+    let serialized = serde_json::to_vec(value).expect("failed to serialize json");
+    out.write_arg(&serialized);
+}
+
+/// A posted message with a button that opens a form.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Launcher {
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    /// The text of the button; follows the title of the form if not set.
+    pub label: Option<String>,
+    pub style: ButtonStyle,
+    pub emoji: Option<ReactionType>,
+    pub content: Option<String>,
+}
+
+impl Launcher {
+    pub fn button(&self, form: &Form) -> CreateButton {
+        let mut button = CreateButton::new(format!("{}{}", crate::event_handler::CUSTOM_ID_PREFIX, form.id))
+            .label(self.label.as_deref().unwrap_or(form.title()))
+            .style(self.style)
+            .disabled(form.paused);
+
+        if let Some(emoji) = &self.emoji {
+            button = button.emoji(emoji.clone());
+        }
+
+        button
+    }
+}
+
+impl FromRedisValue for Launcher {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        from_json_value(v, "not valid launcher json")
+    }
+}
+
+impl ToRedisArgs for Launcher {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        write_json_arg(self, out)
+    }
+}
+
+/// The maximum amount of forms on a panel; the limit of both buttons and select menu options.
+pub const PANEL_MAX_ENTRIES: usize = 25;
+
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum PanelKind {
+    Buttons,
+    Select,
+}
+
+/// A form on a panel.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PanelEntry {
+    pub form_id: FormId,
+    /// The text of the button or option; follows the title of the form if not set.
+    pub label: Option<String>,
+    pub style: ButtonStyle,
+    pub emoji: Option<ReactionType>,
+    /// Shown below the option in select menus.
+    pub description: Option<String>,
+}
+
+/// A posted message with an embed header and buttons or a select menu for several forms.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Panel {
+    id: PanelId,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub kind: PanelKind,
+    pub title: String,
+    pub description: Option<String>,
+    pub color: Option<Colour>,
+    pub image: Option<String>,
+    entries: Vec<PanelEntry>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum AddEntryError {
+    TooManyEntries,
+    AlreadyAdded,
+}
+
+impl Panel {
+    pub fn new(channel_id: ChannelId, kind: PanelKind, title: String) -> Self {
+        Panel {
+            id: PanelId(Uuid::new_v4()),
+            channel_id,
+            message_id: MessageId::default(),
+            kind,
+            title,
+            description: None,
+            color: None,
+            image: None,
+            entries: vec![],
+        }
+    }
+
+    pub fn id(&self) -> PanelId {
+        self.id
+    }
+
+    pub fn contains(&self, form_id: FormId) -> bool {
+        self.entries.iter().any(|e| e.form_id == form_id)
+    }
+
+    pub fn add_entry(&mut self, entry: PanelEntry) -> Result<(), AddEntryError> {
+        if self.contains(entry.form_id) {
+            return Err(AddEntryError::AlreadyAdded);
+        }
+
+        if self.entries.len() >= PANEL_MAX_ENTRIES {
+            return Err(AddEntryError::TooManyEntries);
+        }
+
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn remove_entry(&mut self, form_id: FormId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|e| e.form_id != form_id);
+        self.entries.len() != len
+    }
+
+    pub fn embed(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::new().title(&self.title);
+
+        if let Some(description) = &self.description {
+            embed = embed.description(description);
+        }
+
+        if let Some(color) = self.color {
+            embed = embed.color(color);
+        }
+
+        if let Some(image) = &self.image {
+            embed = embed.image(image);
+        }
+
+        embed
+    }
+
+    /// Creates the buttons or select menu of the panel. Entries of deleted forms are skipped
+    /// and paused forms are disabled (or left out of select menus).
+    pub fn components(&self, forms: &[Form], placeholder: &str) -> Vec<CreateActionRow> {
+        let entries = self.entries.iter()
+            .filter_map(|e| forms.iter().find(|f| f.id == e.form_id).map(|f| (e, f)));
+
+        match self.kind {
+            PanelKind::Buttons => {
+                let buttons = entries
+                    .map(|(entry, form)| {
+                        let mut button = CreateButton::new(format!("{}{}", crate::event_handler::CUSTOM_ID_PREFIX, form.id))
+                            .label(entry.label.as_deref().unwrap_or(form.title()))
+                            .style(entry.style)
+                            .disabled(form.paused);
+
+                        if let Some(emoji) = &entry.emoji {
+                            button = button.emoji(emoji.clone());
+                        }
+
+                        button
+                    })
+                    .collect::<Vec<_>>();
+
+                buttons.chunks(5).map(|row| CreateActionRow::Buttons(row.to_vec())).collect()
+            }
+            PanelKind::Select => {
+                let options = entries
+                    .filter(|(_, form)| !form.paused)
+                    .map(|(entry, form)| {
+                        let mut option = CreateSelectMenuOption::new(entry.label.as_deref().unwrap_or(form.title()), form.id.to_string());
+
+                        if let Some(emoji) = &entry.emoji {
+                            option = option.emoji(emoji.clone());
+                        }
+
+                        if let Some(description) = &entry.description {
+                            option = option.description(description);
+                        }
+
+                        option
+                    })
+                    .collect::<Vec<_>>();
+
+                if options.is_empty() {
+                    return vec![];
+                }
+
+                let select_menu = CreateSelectMenu::new(crate::event_handler::SELECT_CUSTOM_ID, CreateSelectMenuKind::String { options })
+                    .placeholder(placeholder);
+                vec![CreateActionRow::SelectMenu(select_menu)]
+            }
+        }
+    }
+}
+
+impl FromRedisValue for Panel {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        from_json_value(v, "not valid panel json")
+    }
+}
+
+impl ToRedisArgs for Panel {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        write_json_arg(self, out)
+    }
+}
+
+/// Settings which apply to every form in a guild.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GuildSettings {
+    /// The locale used for messages posted in the guild; defaults to the guild's Discord locale.
+    pub language: Option<String>,
+    /// The channel transcripts of closed submissions are posted to.
+    #[serde(default)]
+    pub transcript_channel: Option<ChannelId>,
+    /// Whether submitters are also sent the transcript when their submission is closed.
+    #[serde(default)]
+    pub transcript_dm: bool,
+    /// The terms which may not appear in answers to any form of the guild.
+    #[serde(default)]
+    pub word_filter: WordFilter,
+    /// The channel submissions matching blocked terms are reported to.
+    #[serde(default)]
+    pub filter_log_channel: Option<ChannelId>,
+}
+
+impl FromRedisValue for GuildSettings {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        from_json_value(v, "not valid settings json")
+    }
+}
+
+impl ToRedisArgs for GuildSettings {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        write_json_arg(self, out)
+    }
+}
+
+/// The most terms a guild or form can block.
+pub const MAX_BLOCKED_TERMS: usize = 100;
+/// Keeps blocked patterns small, so checking answers against them stays fast.
+const BLOCKED_PATTERN_SIZE_LIMIT: usize = 1 << 16;
+
+/// A term which may not appear in answers; both kinds ignore case.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum BlockedTerm {
+    /// Matches the text as a whole word.
+    Word(String),
+    /// Matches a regular expression.
+    Pattern(String),
+}
+
+impl BlockedTerm {
+    pub fn text(&self) -> &str {
+        match self {
+            BlockedTerm::Word(text) | BlockedTerm::Pattern(text) => text,
+        }
+    }
+
+    pub fn regex(&self) -> Result<Regex, regex::Error> {
+        let pattern = match self {
+            BlockedTerm::Word(word) => {
+                // Word boundaries only make sense next to word characters, e.g. not after "f*"
+                let boundary = |c: Option<char>| if c.is_some_and(|c| c.is_alphanumeric() || c == '_') { r"\b" } else { "" };
+                format!("{}{}{}", boundary(word.chars().next()), regex::escape(word), boundary(word.chars().last()))
+            }
+            BlockedTerm::Pattern(pattern) => pattern.clone(),
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .size_limit(BLOCKED_PATTERN_SIZE_LIMIT)
+            .build()
+    }
+}
+
+/// What happens to submissions containing a blocked term.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum FilterAction {
+    /// The submission is not posted and the submitter is told why.
+    #[default]
+    Reject,
+    /// The submission is posted with the terms redacted and a warning for staff.
+    Flag,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct WordFilter {
+    terms: Vec<BlockedTerm>,
+    pub action: FilterAction,
+}
+
+#[derive(Debug)]
+pub enum AddTermError {
+    TooManyTerms,
+    InvalidPattern(regex::Error),
+}
+
+impl WordFilter {
+    pub fn terms(&self) -> &[BlockedTerm] {
+        &self.terms
+    }
+
+    /// Blocks a term; returns whether it was not already blocked.
+    pub fn add_term(&mut self, term: BlockedTerm) -> Result<bool, AddTermError> {
+        if self.terms.contains(&term) {
+            return Ok(false);
+        }
+
+        if self.terms.len() >= MAX_BLOCKED_TERMS {
+            return Err(AddTermError::TooManyTerms);
+        }
+
+        term.regex().map_err(AddTermError::InvalidPattern)?;
+        self.terms.push(term);
+        Ok(true)
+    }
+
+    /// Removes a term of either kind; returns whether it was blocked.
+    pub fn remove_term(&mut self, text: &str) -> bool {
+        let length = self.terms.len();
+        self.terms.retain(|t| t.text() != text);
+        self.terms.len() != length
+    }
+}
+
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub enum SerializableMention {
+    Role(RoleId),
+    User(UserId),
+}
+
+impl SerializableMention {
+    fn mention(&self) -> Mention {
+        match *self {
+            SerializableMention::Role(r) => Mention::Role(r),
+            SerializableMention::User(u) => Mention::User(u),
+        }
+    }
+}
+
+impl Display for SerializableMention {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.mention(), f)
+    }
+}
+
+#[async_trait]
+impl poise::SlashArgument for SerializableMention {
+    async fn extract(_ctx: &Context, _interaction: &CommandInteraction, value: &ResolvedValue<'_>) -> Result<Self, SlashArgError> {
+        match value {
+            ResolvedValue::Role(r) => Ok(SerializableMention::Role(r.id)),
+            ResolvedValue::User(u, _) => Ok(SerializableMention::User(u.id)),
+            _ => Err(SlashArgError::new_command_structure_mismatch("expected mentionable")),
+        }
+    }
+
+    fn create(builder: CreateCommandOption) -> CreateCommandOption {
+        builder.kind(CommandOptionType::Mentionable)
+    }
+}
+
+fn localize<'a, T, F>(translations: &'a BTreeMap<String, T>, locale: &str, get: F) -> Option<&'a str>
+where
+    F: Fn(&'a T) -> Option<&'a str>,
+{
+    crate::locales::candidates(locale, translations.keys().map(String::as_str)).into_iter()
+        .find_map(|l| get(&translations[l]))
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct FieldTranslation {
+    name: Option<String>,
+    placeholder: Option<String>,
+}
+
+impl FieldTranslation {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.placeholder.is_none()
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct FormField {
+    #[serde(default)]
+    id: FieldId,
+    name: String,
+    pub style: InputTextStyle,
+    placeholder: Option<String>,
+    pub min_length: Option<u16>,
+    pub max_length: Option<u16>,
+    pub required: bool,
+    pub inline: bool,
+    #[serde(default)]
+    translations: BTreeMap<String, FieldTranslation>,
+    /// The page (modal) the field is shown on, counting from 0.
+    #[serde(default)]
+    page: u8,
+    /// An answer to a field on an earlier page which decides whether the field is shown or required.
+    #[serde(default)]
+    condition: Option<(ConditionEffect, Condition)>,
+    /// The correct answers if the field is a quiz question.
+    #[serde(default)]
+    quiz: Option<QuizAnswer>,
+}
+
+impl FormField {
+    pub fn new(name: String, style: InputTextStyle) -> Result<Self, ValueTooLong> {
+        Self::validate_name(&name)?;
+
+        Ok(Self {
+            id: FieldId(Uuid::new_v4()),
+            name,
+            style,
+            placeholder: None,
+            min_length: None,
+            max_length: None,
+            required: true,
+            inline: false,
+            translations: BTreeMap::new(),
+            page: 0,
+            condition: None,
+            quiz: None,
+        })
+    }
+
+    pub fn id(&self) -> FieldId {
+        self.id
+    }
+
+    pub fn page(&self) -> u8 {
+        self.page
+    }
+
+    /// Sets the page a new field is added to.
+    pub fn set_page(&mut self, page: u8) {
+        self.page = page;
+    }
+
+    pub fn condition(&self) -> Option<&(ConditionEffect, Condition)> {
+        self.condition.as_ref()
+    }
+
+    pub fn quiz(&self) -> Option<&QuizAnswer> {
+        self.quiz.as_ref()
+    }
+
+    /// Makes the field a quiz question, or an ordinary field again if `None`.
+    /// Only short fields can be questions, as longer answers can hardly be compared.
+    pub fn set_quiz(&mut self, quiz: Option<QuizAnswer>) -> Result<(), NotAQuestion> {
+        if quiz.is_some() && self.style != InputTextStyle::Short {
+            return Err(NotAQuestion);
+        }
+
+        self.quiz = quiz.filter(|q| !q.answers.is_empty());
+        Ok(())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn localized_name(&self, locale: &str) -> &str {
+        localize(&self.translations, locale, |t| t.name.as_deref()).unwrap_or(&self.name)
+    }
+
+    pub fn localized_placeholder(&self, locale: &str) -> Option<&str> {
+        localize(&self.translations, locale, |t| t.placeholder.as_deref()).or(self.placeholder())
+    }
+
+    pub fn translations(&self) -> &BTreeMap<String, FieldTranslation> {
+        &self.translations
+    }
+
+    pub fn set_translation(&mut self, locale: String, name: Option<String>, placeholder: Option<String>) -> Result<(), ValueTooLong> {
+        if let Some(name) = &name {
+            Self::validate_name(name)?;
+        }
+
+        if placeholder.as_ref().map(|p| p.len() > PLACEHOLDER_MAX_LENGTH).unwrap_or(false) {
+            return Err(ValueTooLong);
+        }
+
+        let translation = FieldTranslation { name, placeholder };
+        if translation.is_empty() {
+            self.translations.remove(&locale);
+        } else {
+            self.translations.insert(locale, translation);
+        }
+        Ok(())
+    }
+
+    pub fn remove_translation(&mut self, locale: &str) -> bool {
+        self.translations.remove(locale).is_some()
+    }
+
+    pub fn set_name(&mut self, name: String) -> Result<(), ValueTooLong> {
+        Self::validate_name(&name)?;
+
+        self.name = name;
+        Ok(())
+    }
+
+    pub fn placeholder(&self) -> Option<&str> {
+        self.placeholder.as_deref()
+    }
+
+    pub fn set_placeholder(&mut self, placeholder: Option<String>) -> Result<(), ValueTooLong> {
+        if placeholder.as_ref().map(|p| p.len() > PLACEHOLDER_MAX_LENGTH).unwrap_or(false) {
+            return Err(ValueTooLong);
+        }
+
+        self.placeholder = placeholder;
+        Ok(())
+    }
+
+    /// The longest answer the style of the field allows.
+    pub fn response_max_length(&self) -> u16 {
+        match self.style {
+            InputTextStyle::Paragraph => PARAGRAPH_RESPONSE_MAX_LENGTH,
+            _ => FIELD_RESPONSE_MAX_LENGTH,
+        }
+    }
+
+    fn input_text<T: Into<String>>(&self, custom_id: T, locale: &str, value: Option<&str>, required: bool) -> CreateInputText {
+        let max_length = self.max_length.map_or(self.response_max_length(), |l| l.min(self.response_max_length()));
+        let mut builder = CreateInputText::new(self.style, self.localized_name(locale), custom_id)
+            .max_length(max_length)
+            .required(required);
+
+        if let Some(placeholder) = self.localized_placeholder(locale) {
+            builder = builder.placeholder(placeholder);
+        }
+
+        if let Some(min_length) = self.min_length {
+            builder = builder.min_length(min_length);
+        }
+
+        if let Some(value) = value {
+            builder = builder.value(value.chars().take(max_length as usize).collect::<String>());
+        }
+
+        builder
+    }
+
+    pub fn apply_to_embed(&self, embed: CreateEmbed, value: String, locale: &str) -> CreateEmbed {
+        embed.field(self.localized_name(locale), value, self.inline)
+    }
+
+    fn validate_name(name: &str) -> Result<(), ValueTooLong> {
+        if name.len() > LABEL_MAX_LENGTH {
+            Err(ValueTooLong)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// How an answer is compared with the value of a condition; comparisons ignore case and surrounding whitespace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ConditionOperator {
+    Equals,
+    NotEquals,
+    Contains,
+}
+
+/// A test of the answer to an earlier field.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Condition {
+    pub field: FieldId,
+    pub operator: ConditionOperator,
+    pub value: String,
+}
+
+impl Condition {
+    /// Whether the answers given so far satisfy the condition; a missing answer counts as empty.
+    pub fn matches(&self, answers: &HashMap<FieldId, String>) -> bool {
+        let answer = answers.get(&self.field).map_or("", |a| a.trim()).to_lowercase();
+        let value = self.value.trim().to_lowercase();
+        match self.operator {
+            ConditionOperator::Equals => answer == value,
+            ConditionOperator::NotEquals => answer != value,
+            ConditionOperator::Contains => answer.contains(&value),
+        }
+    }
+}
+
+/// What a field's condition decides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ConditionEffect {
+    /// The field is only shown if the condition holds.
+    Show,
+    /// The field is always shown, but only required if the condition holds.
+    Require,
+}
+
+/// The accepted answers to a quiz question and the points a correct answer is worth.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct QuizAnswer {
+    pub answers: Vec<String>,
+    pub points: u32,
+}
+
+impl QuizAnswer {
+    /// Whether an answer is one of the accepted ones, ignoring case and surrounding whitespace.
+    pub fn is_correct(&self, answer: &str) -> bool {
+        let answer = answer.trim().to_lowercase();
+        self.answers.iter().any(|a| a.trim().to_lowercase() == answer)
+    }
+}
+
+/// How submissions of a quiz are judged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct QuizSettings {
+    /// The points needed to pass.
+    pub threshold: u32,
+    /// Whether submissions are accepted or denied right away instead of being left to staff.
+    pub auto_decide: bool,
+    /// The role given to submitters who pass, if submissions are decided automatically.
+    pub pass_role: Option<RoleId>,
+}
+
+/// Allows at most `count` submissions by a user within any `window`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Quota {
+    pub count: u32,
+    pub window: Duration,
+}
+
+/// The calendar period after which a calendar cooldown resets, along with the day it resets on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ResetPeriod {
+    Daily,
+    Weekly(Weekday),
+    /// The day of the month; at most 28 so that it exists in every month.
+    Monthly(u32),
+}
+
+/// A cooldown which ends at fixed points in the calendar rather than a duration after submitting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CalendarCooldown {
+    pub period: ResetPeriod,
+    pub time: NaiveTime,
+    pub timezone: Tz,
+}
+
+impl CalendarCooldown {
+    /// Finds the first reset strictly after the given time.
+    pub fn next_reset(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let today = now.with_timezone(&self.timezone).date_naive();
+        let mut date = match self.period {
+            ResetPeriod::Daily => today,
+            ResetPeriod::Weekly(weekday) => {
+                let days = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+                today + Days::new(days as u64)
+            }
+            ResetPeriod::Monthly(day) => today.with_day(day).expect("invalid day of month"),
+        };
+
+        loop {
+            let reset = self.reset_on(date);
+            if reset > now {
+                return reset;
+            }
+
+            date = match self.period {
+                ResetPeriod::Daily => date + Days::new(1),
+                ResetPeriod::Weekly(_) => date + Days::new(7),
+                ResetPeriod::Monthly(_) => date + Months::new(1),
+            };
+        }
+    }
+
+    fn reset_on(&self, date: NaiveDate) -> DateTime<Utc> {
+        let local = date.and_time(self.time);
+        self.timezone.from_local_datetime(&local).earliest()
+            // The reset point was skipped by a daylight saving transition, so reset once it is over
+            .or_else(|| self.timezone.from_local_datetime(&(local + TimeDelta::hours(1))).earliest())
+            .expect("daylight saving transitions last at most an hour")
+            .with_timezone(&Utc)
+    }
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct FormTranslation {
+    title: Option<String>,
+    description: Option<String>,
+}
+
+impl FormTranslation {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.description.is_none()
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Form {
+    id: FormId,
+    title: String,
+    description: Option<String>,
+    fields: Vec<FormField>,
+    pub destination: ChannelId,
+    pub mention: Option<SerializableMention>,
+    cooldown: Option<Duration>,
+    #[serde(default)]
+    translations: BTreeMap<String, FormTranslation>,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    cooldown_bypass_roles: Vec<RoleId>,
+    #[serde(default)]
+    quota: Option<Quota>,
+    #[serde(default)]
+    calendar_cooldown: Option<CalendarCooldown>,
+    /// Incremented whenever fields are added, removed or moved.
+    #[serde(default)]
+    revision: u32,
+    /// Whether the modal is filled in with the answers of the user's previous submission.
+    #[serde(default)]
+    pub prefill: bool,
+    /// Whether submissions are posted without revealing who sent them.
+    #[serde(default)]
+    pub anonymous: bool,
+    /// Whether staff talk to the submitter through the bot instead of adding them to the thread.
+    #[serde(default)]
+    pub relay: bool,
+    /// Conditions for showing pages, by page.
+    #[serde(default)]
+    page_conditions: BTreeMap<u8, Condition>,
+    /// The pass threshold of the form if it is used as a quiz.
+    #[serde(default)]
+    pub quiz: Option<QuizSettings>,
+    /// The terms which may not appear in answers to this form, in addition to those of the guild.
+    #[serde(default)]
+    pub word_filter: WordFilter,
+}
+
+impl FromRedisValue for Form {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let mut form: Form = from_json_value(v, "not valid form json")?;
+
+        // Fields created before they had ids get one based on their position, which stays the
+        // same until the form is saved along with the id
+        for (i, field) in form.fields.iter_mut().enumerate().filter(|(_, f)| f.id == FieldId::default()) {
+            field.id = FieldId(Uuid::from_u128(form.id.0.as_u128() ^ (i as u128 + 1)));
+        }
+
+        Ok(form)
+    }
+}
+
+impl ToRedisArgs for Form {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        write_json_arg(self, out)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct NotAQuestion;
+
+impl Display for NotAQuestion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "only short fields can be quiz questions")
+    }
+}
+
+impl std::error::Error for NotAQuestion {}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum AddFieldError {
+    TooManyFields,
+    IllegalAddBefore,
+    /// A field cannot be moved among the fields of another page.
+    OtherPage,
+}
+
+impl Display for AddFieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddFieldError::TooManyFields => write!(f, "too many fields"),
+            AddFieldError::IllegalAddBefore => write!(f, "illegal add-before target"),
+            AddFieldError::OtherPage => write!(f, "position on another page"),
+        }
+    }
+}
+
+impl std::error::Error for AddFieldError {}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ConditionError {
+    UnknownField,
+    /// Conditions can only depend on answers to fields on earlier pages.
+    NotOnEarlierPage,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum PageError {
+    PageFull,
+    /// Moving the field would leave a condition depending on a field which is not on an earlier page.
+    BreaksCondition,
+}
+
+#[derive(Debug)]
+pub struct ValueTooLong;
+
+impl Display for ValueTooLong {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "field too long")
+    }
+}
+
+impl std::error::Error for ValueTooLong {}
 
 impl Form {
     pub fn new<C: Into<ChannelId>>(title: String, destination: C) -> Result<Self, ValueTooLong> {
@@ -314,40 +1551,162 @@ impl Form {
             destination: destination.into(),
             mention: None,
             cooldown: None,
+            translations: BTreeMap::new(),
+            paused: false,
+            cooldown_bypass_roles: vec![],
+            quota: None,
+            calendar_cooldown: None,
+            revision: 0,
+            prefill: false,
+            anonymous: false,
+            relay: false,
+            page_conditions: BTreeMap::new(),
+            quiz: None,
+            word_filter: WordFilter::default(),
         })
     }
 
-    pub fn title(&self) -> &str {
-        &self.title
+    pub fn id(&self) -> FormId {
+        self.id
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn localized_title(&self, locale: &str) -> &str {
+        localize(&self.translations, locale, |t| t.title.as_deref()).unwrap_or(&self.title)
+    }
+
+    pub fn set_title(&mut self, title: String) -> Result<(), ValueTooLong> {
+        Self::validate_title(&title)?;
+        self.title = title;
+        Ok(())
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn set_description(&mut self, description: Option<String>) -> Result<(), ValueTooLong> {
+        if let Some(true) = description.as_ref().map(|d| d.len() > 4096) {
+            return Err(ValueTooLong);
+        }
+
+        self.description = description;
+        Ok(())
+    }
+
+    pub fn localized_description(&self, locale: &str) -> Option<&str> {
+        localize(&self.translations, locale, |t| t.description.as_deref()).or(self.description())
     }
 
-    pub fn set_title(&mut self, title: String) -> Result<(), ValueTooLong> {
-        Self::validate_title(&title)?;
-        self.title = title;
-        Ok(())
+    /// All locales that the form or any of its fields are translated to.
+    pub fn locales(&self) -> Vec<&str> {
+        let mut locales = self.translations.keys()
+            .chain(self.fields.iter().flat_map(|f| f.translations.keys()))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        locales.sort_unstable();
+        locales.dedup();
+        locales
     }
 
-    pub fn description(&self) -> Option<&str> {
-        self.description.as_deref()
-    }
+    pub fn set_translation(&mut self, locale: String, title: Option<String>, description: Option<String>) -> Result<(), ValueTooLong> {
+        if let Some(title) = &title {
+            Self::validate_title(title)?;
+        }
 
-    pub fn set_description(&mut self, description: Option<String>) -> Result<(), ValueTooLong> {
         if let Some(true) = description.as_ref().map(|d| d.len() > 4096) {
             return Err(ValueTooLong);
         }
 
-        self.description = description;
+        let translation = FormTranslation { title, description };
+        if translation.is_empty() {
+            self.translations.remove(&locale);
+        } else {
+            self.translations.insert(locale, translation);
+        }
         Ok(())
     }
 
+    /// Removes all translations for a locale from the form and its fields.
+    pub fn remove_translation(&mut self, locale: &str) -> bool {
+        let mut removed = self.translations.remove(locale).is_some();
+        for field in &mut self.fields {
+            removed |= field.remove_translation(locale);
+        }
+        removed
+    }
+
     pub fn cooldown(&self) -> Option<Duration> {
         self.cooldown
     }
 
+    /// Sets the rolling cooldown of the form, replacing any calendar cooldown.
     pub fn set_cooldown(&mut self, cooldown: Option<Duration>) {
         self.cooldown = cooldown
             .map(|d| Duration::from_secs(d.as_secs()))
             .filter(|d| !d.is_zero());
+        if self.cooldown.is_some() {
+            self.calendar_cooldown = None;
+        }
+    }
+
+    /// When a cooldown started by submitting the form now would end, if the form has a cooldown.
+    pub fn cooldown_expiry(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match (self.cooldown, self.calendar_cooldown) {
+            (_, Some(calendar)) => Some(calendar.next_reset(now)),
+            (Some(duration), None) => Some(now + duration),
+            (None, None) => None,
+        }
+    }
+
+    pub fn calendar_cooldown(&self) -> Option<CalendarCooldown> {
+        self.calendar_cooldown
+    }
+
+    /// Sets the calendar cooldown of the form, replacing any rolling cooldown.
+    pub fn set_calendar_cooldown(&mut self, calendar_cooldown: Option<CalendarCooldown>) {
+        self.calendar_cooldown = calendar_cooldown;
+        if self.calendar_cooldown.is_some() {
+            self.cooldown = None;
+        }
+    }
+
+    pub fn quota(&self) -> Option<Quota> {
+        self.quota
+    }
+
+    pub fn set_quota(&mut self, quota: Option<Quota>) {
+        self.quota = quota
+            .map(|q| Quota { count: q.count, window: Duration::from_secs(q.window.as_secs()) })
+            .filter(|q| q.count > 0 && !q.window.is_zero());
+    }
+
+    pub fn cooldown_bypass_roles(&self) -> &[RoleId] {
+        &self.cooldown_bypass_roles
+    }
+
+    /// Lets members with the role skip the cooldown; returns whether it was added.
+    pub fn add_cooldown_bypass_role(&mut self, role_id: RoleId) -> bool {
+        if self.cooldown_bypass_roles.contains(&role_id) {
+            return false;
+        }
+
+        self.cooldown_bypass_roles.push(role_id);
+        true
+    }
+
+    pub fn remove_cooldown_bypass_role(&mut self, role_id: RoleId) -> bool {
+        let length = self.cooldown_bypass_roles.len();
+        self.cooldown_bypass_roles.retain(|r| *r != role_id);
+        self.cooldown_bypass_roles.len() != length
+    }
+
+    /// Whether a member with the given roles skips the cooldown of the form.
+    pub fn bypasses_cooldown(&self, roles: &[RoleId]) -> bool {
+        roles.iter().any(|r| self.cooldown_bypass_roles.contains(r))
     }
 
     pub fn fields(&self) -> &[FormField] {
@@ -356,16 +1715,123 @@ impl Form {
 
     pub fn fields_mut(&mut self) -> &mut [FormField] { self.fields.as_mut_slice() }
 
-    pub fn quick_modal(&self) -> Option<CreateQuickModal> {
-        if self.fields.is_empty() {
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    /// The number of pages of the form, including empty pages between others.
+    pub fn page_count(&self) -> u8 {
+        self.fields.last().map_or(0, |f| f.page + 1)
+    }
+
+    pub fn page_conditions(&self) -> &BTreeMap<u8, Condition> {
+        &self.page_conditions
+    }
+
+    /// Whether the field at an index is shown, given the answers so far.
+    pub fn is_shown(&self, index: usize, answers: &HashMap<FieldId, String>) -> bool {
+        let field = &self.fields[index];
+        self.page_conditions.get(&field.page).is_none_or(|c| c.matches(answers))
+            && !matches!(&field.condition, Some((ConditionEffect::Show, c)) if !c.matches(answers))
+    }
+
+    fn is_required(&self, field: &FormField, answers: &HashMap<FieldId, String>) -> bool {
+        match &field.condition {
+            Some((ConditionEffect::Require, c)) => c.matches(answers),
+            _ => field.required,
+        }
+    }
+
+    /// Whether every required field shown on the pages before the given one has been answered.
+    pub fn answered_before(&self, page: u8, answers: &HashMap<FieldId, String>) -> bool {
+        self.fields.iter().enumerate()
+            .filter(|(i, f)| f.page < page && self.is_shown(*i, answers) && self.is_required(f, answers))
+            .all(|(_, f)| answers.get(&f.id).is_some_and(|a| !a.is_empty()))
+    }
+
+    /// Finds the first page after the given one (or the first page) with any field to show.
+    pub fn next_page(&self, after: Option<u8>, answers: &HashMap<FieldId, String>) -> Option<u8> {
+        self.fields.iter().enumerate()
+            .filter(|(_, f)| after.is_none_or(|p| f.page > p))
+            .find(|(i, _)| self.is_shown(*i, answers))
+            .map(|(_, f)| f.page)
+    }
+
+    /// Builds the modal of the first page of the form, with the inputs filled in from earlier
+    /// answers if given.
+    pub fn modal(&self, kind: ModalKind, locale: &str, prefill: &HashMap<FieldId, String>) -> Option<CreateModal> {
+        let page = self.next_page(None, &HashMap::new())?;
+        self.page_modal(kind, page, locale, prefill, &HashMap::new())
+    }
+
+    /// Builds the modal of a page with the fields shown given the answers to the earlier pages.
+    pub fn page_modal(&self, kind: ModalKind, page: u8, locale: &str, prefill: &HashMap<FieldId, String>, answers: &HashMap<FieldId, String>) -> Option<CreateModal> {
+        let components = self.fields.iter().enumerate()
+            .filter(|(i, f)| f.page == page && self.is_shown(*i, answers))
+            .map(|(i, f)| {
+                let value = prefill.get(&f.id).map(String::as_str);
+                CreateActionRow::InputText(f.input_text(i.to_string(), locale, value, self.is_required(f, answers)))
+            })
+            .collect::<Vec<_>>();
+        if components.is_empty() {
+            return None;
+        }
+
+        let custom_id = ModalId { kind, form_id: self.id, revision: self.revision, page };
+        let title = self.localized_title(locale);
+        let paged_title = format!("{title} ({}/{})", page + 1, self.page_count());
+        let title = if self.page_count() > 1 && paged_title.chars().count() <= LABEL_MAX_LENGTH { paged_title } else { title.to_owned() };
+        Some(CreateModal::new(custom_id.to_string(), title).components(components))
+    }
+
+    /// Whether all fields fit in a single modal, so the answers can be edited in one go.
+    pub fn is_single_page(&self) -> bool {
+        self.page_count() <= 1 && self.page_conditions.is_empty() && self.fields.iter().all(|f| f.condition.is_none())
+    }
+
+    /// Builds a modal of a single page form which is handled by something other than a [`ModalId`].
+    pub fn custom_modal(&self, custom_id: String, locale: &str, answers: &HashMap<FieldId, String>) -> Option<CreateModal> {
+        if self.fields.is_empty() || !self.is_single_page() {
             return None;
         }
 
-        let builder = CreateQuickModal::new(&self.title)
-            .timeout(Duration::from_secs(600));
+        let components = self.fields.iter().enumerate()
+            .map(|(i, f)| CreateActionRow::InputText(f.input_text(i.to_string(), locale, answers.get(&f.id).map(String::as_str), f.required)))
+            .collect();
+        Some(CreateModal::new(custom_id, self.localized_title(locale)).components(components))
+    }
+
+    /// Lists the answers in the order of the fields, leaving those of hidden fields empty.
+    pub fn ordered_answers(&self, answers: &HashMap<FieldId, String>) -> Vec<String> {
+        self.fields.iter().enumerate()
+            .map(|(i, f)| if self.is_shown(i, answers) { answers.get(&f.id).cloned().unwrap_or_default() } else { String::new() })
+            .collect()
+    }
+
+    /// Scores the answers to the quiz questions which were shown, or returns `None` if the form
+    /// has no questions.
+    pub fn grade(&self, answers: &[String]) -> Option<QuizResult> {
+        let answers_by_field = self.answers_by_field(answers);
+        let (points, max) = self.fields.iter().zip(answers).enumerate()
+            .filter(|(i, _)| self.is_shown(*i, &answers_by_field))
+            .filter_map(|(_, (f, a))| f.quiz.as_ref().map(|q| (q, a)))
+            .fold(None, |total: Option<(u32, u32)>, (question, answer)| {
+                let (points, max) = total.unwrap_or_default();
+                let earned = if question.is_correct(answer) { question.points } else { 0 };
+                Some((points + earned, max + question.points))
+            })?;
+
+        Some(QuizResult {
+            points,
+            max,
+            passed: self.quiz.map(|q| points >= q.threshold),
+            decided: false,
+        })
+    }
 
-        Some(self.fields.iter().enumerate()
-            .fold(builder, |acc, (i, f)| acc.field(f.input_text(i.to_string()))))
+    /// Pairs the answers of a modal with the ids of the fields they answer.
+    pub fn answers_by_field(&self, answers: &[String]) -> HashMap<FieldId, String> {
+        self.fields.iter().zip(answers).map(|(f, a)| (f.id, a.clone())).collect()
     }
 
     pub fn add_field(
@@ -373,7 +1839,7 @@ impl Form {
         field: FormField,
         add_before: Option<usize>,
     ) -> Result<(), AddFieldError> {
-        if self.fields.len() >= 5 {
+        if self.fields.iter().filter(|f| f.page == field.page).count() >= PAGE_MAX_FIELDS {
             return Err(AddFieldError::TooManyFields);
         }
 
@@ -387,12 +1853,28 @@ impl Form {
             self.fields.push(field);
         }
 
+        // Fields are kept in the order of their pages, so answers are too
+        self.fields.sort_by_key(|f| f.page);
+        self.revision += 1;
         Ok(())
     }
 
+    /// Whether a field inserted at an index would be among the fields of its page.
+    fn is_on_page(&self, index: usize, page: u8) -> bool {
+        (index == 0 || self.fields[index - 1].page <= page) && self.fields.get(index).is_none_or(|f| f.page >= page)
+    }
+
+    /// Removes a field, along with any conditions depending on it.
     pub fn remove_field(&mut self, index: usize) -> bool {
         if index < self.fields.len() {
-            self.fields.remove(index);
+            let id = self.fields.remove(index).id;
+            self.page_conditions.retain(|_, c| c.field != id);
+            for field in &mut self.fields {
+                if field.condition.as_ref().is_some_and(|(_, c)| c.field == id) {
+                    field.condition = None;
+                }
+            }
+            self.revision += 1;
             true
         } else {
             false
@@ -408,12 +1890,93 @@ impl Form {
             return Err(AddFieldError::IllegalAddBefore);
         }
 
+        // Fields stay on their page; moving them to another one is up to `set_field_page`
         let field = self.fields.remove(index);
+        if !self.is_on_page(destination, field.page) {
+            self.fields.insert(index, field);
+            return Err(AddFieldError::OtherPage);
+        }
         self.add_field(field, Some(destination))?;
 
         Ok(true)
     }
 
+    /// Checks that a condition for a page depends on a field on an earlier page.
+    fn check_condition(&self, page: u8, condition: &Condition) -> Result<(), ConditionError> {
+        match self.fields.iter().find(|f| f.id == condition.field) {
+            None => Err(ConditionError::UnknownField),
+            Some(f) if f.page >= page => Err(ConditionError::NotOnEarlierPage),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Sets the condition of the field at an index, depending on the answer to the field at
+    /// another index. Returns whether the field exists.
+    pub fn set_field_condition(&mut self, index: usize, condition: Option<(ConditionEffect, usize, ConditionOperator, String)>) -> Result<bool, ConditionError> {
+        let Some(page) = self.fields.get(index).map(|f| f.page) else {
+            return Ok(false);
+        };
+
+        let condition = match condition {
+            Some((effect, depends_on, operator, value)) => {
+                let field = self.fields.get(depends_on).ok_or(ConditionError::UnknownField)?.id;
+                let condition = Condition { field, operator, value };
+                self.check_condition(page, &condition)?;
+                Some((effect, condition))
+            }
+            None => None,
+        };
+
+        self.fields[index].condition = condition;
+        Ok(true)
+    }
+
+    /// Sets the condition for showing a page, depending on the answer to the field at an index.
+    pub fn set_page_condition(&mut self, page: u8, condition: Option<(usize, ConditionOperator, String)>) -> Result<(), ConditionError> {
+        match condition {
+            Some((depends_on, operator, value)) => {
+                let field = self.fields.get(depends_on).ok_or(ConditionError::UnknownField)?.id;
+                let condition = Condition { field, operator, value };
+                self.check_condition(page, &condition)?;
+                self.page_conditions.insert(page, condition);
+            }
+            None => {
+                self.page_conditions.remove(&page);
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the field at an index to another page. Returns whether the field exists.
+    pub fn set_field_page(&mut self, index: usize, page: u8) -> Result<bool, PageError> {
+        let Some(field) = self.fields.get(index) else {
+            return Ok(false);
+        };
+        let (id, previous_page) = (field.id, field.page);
+        if previous_page == page {
+            return Ok(true);
+        }
+
+        if self.fields.iter().filter(|f| f.page == page).count() >= PAGE_MAX_FIELDS {
+            return Err(PageError::PageFull);
+        }
+
+        self.fields[index].page = page;
+        let conditions_hold = self.fields.iter()
+            .filter_map(|f| f.condition.as_ref().map(|(_, c)| (f.page, c)))
+            .chain(self.page_conditions.iter().map(|(page, c)| (*page, c)))
+            .all(|(page, c)| self.check_condition(page, c).is_ok());
+        if !conditions_hold {
+            let field = self.fields.iter_mut().find(|f| f.id == id).expect("field exists");
+            field.page = previous_page;
+            return Err(PageError::BreaksCondition);
+        }
+
+        self.fields.sort_by_key(|f| f.page);
+        self.revision += 1;
+        Ok(true)
+    }
+
     fn validate_title(title: &str) -> Result<(), ValueTooLong> {
         if title.len() > 256 {
             Err(ValueTooLong)
@@ -423,10 +1986,174 @@ impl Form {
     }
 }
 
+/// The answers of a user to a form, along with what is needed to post them without the interaction.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Submission {
+    pub id: SubmissionId,
+    pub guild_id: GuildId,
+    pub form_id: FormId,
+    pub user_id: UserId,
+    pub user_name: String,
+    pub avatar_url: String,
+    pub locale: String,
+    pub guild_locale: Option<String>,
+    pub answers: Vec<String>,
+    pub submitted_at: Timestamp,
+    /// The revision of the form's fields which the answers belong to.
+    #[serde(default)]
+    pub revision: u32,
+    /// Whether the submitter is hidden from staff; only administrators can look them up.
+    #[serde(default)]
+    pub anonymous: bool,
+    /// The sequential number of the submission within its form, if it was given one.
+    #[serde(default)]
+    pub number: Option<u64>,
+    /// Whether messages are relayed between the thread and the submitter's DMs.
+    #[serde(default)]
+    pub relay: bool,
+    /// The score of the answers if the form is a quiz.
+    #[serde(default)]
+    pub quiz: Option<QuizResult>,
+    /// Whether the answers contained blocked terms, which have been redacted.
+    #[serde(default)]
+    pub flagged: bool,
+}
+
+impl Submission {
+    pub fn form_ref(&self) -> FormRef {
+        FormRef::new(self.guild_id, self.form_id)
+    }
+
+    /// Whether the submitter is added to the thread of the submission.
+    pub fn in_thread(&self) -> bool {
+        !self.anonymous && !self.relay
+    }
+
+    /// The ticket number staff can refer to the submission by, e.g. `#0042`.
+    pub fn ticket(&self) -> Option<String> {
+        self.number.map(format_ticket)
+    }
+}
+
+/// The score of a quiz submission.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuizResult {
+    pub points: u32,
+    pub max: u32,
+    /// Whether the score reaches the pass threshold, if the form had one.
+    pub passed: Option<bool>,
+    /// Whether the submission was accepted or denied automatically, so staff need not review it.
+    pub decided: bool,
+}
+
+pub fn format_ticket(number: u64) -> String {
+    format!("#{number:04}")
+}
+
+/// The usage of a form which is not visible from its submissions.
+#[derive(Clone, Copy)]
+pub enum FormCounter {
+    /// The modal of the form was shown to a user.
+    Opened,
+    /// The modal was submitted and the submission is being posted.
+    Submitted,
+    /// A user could not open or submit the form because of its cooldown or quota.
+    CooldownRejected,
+}
+
+impl FormCounter {
+    fn field(self) -> &'static str {
+        match self {
+            FormCounter::Opened => "opened",
+            FormCounter::Submitted => "submitted",
+            FormCounter::CooldownRejected => "cooldown_rejected",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FormCounters {
+    pub opened: u64,
+    pub submitted: u64,
+    pub cooldown_rejections: u64,
+}
+
+/// A change made by the submitter to their answers.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SubmissionEdit {
+    pub edited_at: Timestamp,
+    pub previous_answers: Vec<String>,
+}
+
+/// A submission which has been posted, along with where it was posted and what happened since.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SubmissionRecord {
+    pub submission: Submission,
+    pub thread_id: ChannelId,
+    pub message_id: MessageId,
+    #[serde(default)]
+    pub history: Vec<SubmissionEdit>,
+    #[serde(default)]
+    pub withdrawn: bool,
+    /// Whether staff have closed the thread; the submission can no longer be changed.
+    #[serde(default)]
+    pub closed: bool,
+    /// When the submission was closed, if it was closed after this was recorded.
+    #[serde(default)]
+    pub closed_at: Option<Timestamp>,
+}
+
+impl FromRedisValue for SubmissionRecord {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        from_json_value(v, "not valid submission json")
+    }
+}
+
+impl ToRedisArgs for SubmissionRecord {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        write_json_arg(self, out)
+    }
+}
+
+/// A submission which could not be posted and is waiting to be retried.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FailedSubmission {
+    pub submission: Submission,
+    pub attempts: u32,
+    pub error: String,
+}
+
+impl FromRedisValue for FailedSubmission {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        from_json_value(v, "not valid failed submission json")
+    }
+}
+
+impl ToRedisArgs for FailedSubmission {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        write_json_arg(self, out)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use serenity::all::{ChannelId, InputTextStyle};
-    use crate::state::{AddFieldError, Form, FormField};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use chrono::{DateTime, NaiveTime, Utc, Weekday};
+    use chrono_tz::Tz;
+    use redis::{FromRedisValue, Value};
+    use serenity::all::{ChannelId, InputTextStyle, RoleId};
+    use serenity::all::ButtonStyle;
+    use uuid::Uuid;
+
+    use crate::state::{AddEntryError, AddFieldError, CalendarCooldown, ConditionEffect, ConditionError, ConditionOperator, FieldId, Form, FormField, FormId, PageError, Panel, PanelEntry, PanelKind, QuizAnswer, QuizResult, QuizSettings, Quota, ResetPeriod, format_ticket, PANEL_MAX_ENTRIES};
 
     #[test]
     fn move_backward() {
@@ -464,6 +2191,320 @@ mod tests {
         assert_eq!(form.move_field(0, 10), Err(AddFieldError::IllegalAddBefore));
     }
 
+    #[test]
+    fn move_to_other_page() {
+        let mut form = create_form();
+        assert_eq!(form.set_field_page(4, 1), Ok(true));
+        assert_eq!(form.set_field_page(3, 1), Ok(true));
+        let names = |form: &Form| form.fields().iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&form), ["Field 0", "Field 1", "Field 2", "Field 3", "Field 4"]);
+
+        assert_eq!(form.move_field(0, 4), Err(AddFieldError::OtherPage));
+        assert_eq!(form.move_field(4, 0), Err(AddFieldError::OtherPage));
+        assert_eq!(names(&form), ["Field 0", "Field 1", "Field 2", "Field 3", "Field 4"]);
+
+        assert!(form.move_field(0, 2).unwrap());
+        assert!(form.move_field(4, 3).unwrap());
+        assert_eq!(names(&form), ["Field 1", "Field 2", "Field 0", "Field 4", "Field 3"]);
+    }
+
+    #[test]
+    fn localized_title_falls_back() {
+        let mut form = create_form();
+        form.set_translation("en-GB".to_owned(), Some("British Title".to_owned()), None).unwrap();
+        form.set_translation("da".to_owned(), None, Some("Beskrivelse".to_owned())).unwrap();
+        assert_eq!(form.localized_title("en-US"), "British Title");
+        assert_eq!(form.localized_title("da"), "My Title");
+        assert_eq!(form.localized_description("da"), Some("Beskrivelse"));
+        assert_eq!(form.localized_description("fr"), None);
+    }
+
+    #[test]
+    fn remove_translation_from_fields() {
+        let mut form = create_form();
+        form.fields_mut()[1].set_translation("da".to_owned(), Some("Felt 1".to_owned()), None).unwrap();
+        assert_eq!(form.fields()[1].localized_name("da"), "Felt 1");
+        assert_eq!(form.locales(), ["da"]);
+        assert!(form.remove_translation("da"));
+        assert_eq!(form.fields()[1].localized_name("da"), "Field 1");
+    }
+
+    #[test]
+    fn panel_entries() {
+        let entry = || PanelEntry {
+            form_id: FormId(Uuid::new_v4()),
+            label: None,
+            style: ButtonStyle::Primary,
+            emoji: None,
+            description: None,
+        };
+
+        let mut panel = Panel::new(ChannelId::new(123), PanelKind::Buttons, "Panel".to_owned());
+        let first = entry();
+        panel.add_entry(first.clone()).unwrap();
+        assert_eq!(panel.add_entry(first.clone()), Err(AddEntryError::AlreadyAdded));
+
+        for _ in 1..PANEL_MAX_ENTRIES {
+            panel.add_entry(entry()).unwrap();
+        }
+        assert_eq!(panel.add_entry(entry()), Err(AddEntryError::TooManyEntries));
+
+        assert!(panel.remove_entry(first.form_id));
+        assert!(!panel.contains(first.form_id));
+    }
+
+    #[test]
+    fn cooldown_bypass_roles() {
+        let mut form = create_form();
+        assert!(form.add_cooldown_bypass_role(RoleId::new(1)));
+        assert!(!form.add_cooldown_bypass_role(RoleId::new(1)));
+
+        assert!(form.bypasses_cooldown(&[RoleId::new(2), RoleId::new(1)]));
+        assert!(!form.bypasses_cooldown(&[RoleId::new(2)]));
+        assert!(!form.bypasses_cooldown(&[]));
+
+        assert!(form.remove_cooldown_bypass_role(RoleId::new(1)));
+        assert!(!form.remove_cooldown_bypass_role(RoleId::new(1)));
+        assert!(!form.bypasses_cooldown(&[RoleId::new(1)]));
+    }
+
+    #[test]
+    fn quota_is_normalized() {
+        let mut form = create_form();
+        form.set_quota(Some(Quota { count: 3, window: Duration::from_millis(86_400_500) }));
+        assert_eq!(form.quota(), Some(Quota { count: 3, window: Duration::from_secs(86_400) }));
+
+        form.set_quota(Some(Quota { count: 0, window: Duration::from_secs(60) }));
+        assert_eq!(form.quota(), None);
+
+        form.set_quota(Some(Quota { count: 3, window: Duration::from_millis(500) }));
+        assert_eq!(form.quota(), None);
+    }
+
+    fn calendar(period: ResetPeriod, time: &str, timezone: Tz) -> CalendarCooldown {
+        CalendarCooldown { period, time: NaiveTime::parse_from_str(time, "%H:%M").unwrap(), timezone }
+    }
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn calendar_cooldown_resets() {
+        let daily = calendar(ResetPeriod::Daily, "18:00", Tz::UTC);
+        assert_eq!(daily.next_reset(utc("2024-05-01T12:00:00Z")), utc("2024-05-01T18:00:00Z"));
+        assert_eq!(daily.next_reset(utc("2024-05-01T18:00:00Z")), utc("2024-05-02T18:00:00Z"));
+
+        // 2024-05-01 is a Wednesday
+        let weekly = calendar(ResetPeriod::Weekly(Weekday::Mon), "00:00", Tz::UTC);
+        assert_eq!(weekly.next_reset(utc("2024-05-01T12:00:00Z")), utc("2024-05-06T00:00:00Z"));
+        assert_eq!(weekly.next_reset(utc("2024-05-06T00:00:00Z")), utc("2024-05-13T00:00:00Z"));
+
+        let monthly = calendar(ResetPeriod::Monthly(1), "00:00", Tz::UTC);
+        assert_eq!(monthly.next_reset(utc("2024-12-15T12:00:00Z")), utc("2025-01-01T00:00:00Z"));
+
+        let copenhagen = calendar(ResetPeriod::Daily, "00:00", Tz::Europe__Copenhagen);
+        assert_eq!(copenhagen.next_reset(utc("2024-05-01T21:00:00Z")), utc("2024-05-01T22:00:00Z"));
+
+        // Clocks skip from 02:00 to 03:00 on 2024-03-31 in Copenhagen
+        let skipped = calendar(ResetPeriod::Daily, "02:30", Tz::Europe__Copenhagen);
+        assert_eq!(skipped.next_reset(utc("2024-03-30T12:00:00Z")), utc("2024-03-31T01:30:00Z"));
+    }
+
+    #[test]
+    fn field_changes_bump_revision() {
+        let mut form = create_form();
+        let revision = form.revision();
+
+        assert!(form.move_field(0, 1).unwrap());
+        assert!(form.revision() > revision);
+
+        let revision = form.revision();
+        assert!(!form.remove_field(10));
+        assert_eq!(form.revision(), revision);
+        assert!(form.remove_field(0));
+        assert!(form.revision() > revision);
+    }
+
+    #[test]
+    fn answers_follow_moved_fields() {
+        let mut form = create_form();
+        let answers = ["a", "b", "c", "d", "e"].map(str::to_owned);
+        let by_field = form.answers_by_field(&answers);
+
+        form.move_field(0, 4).unwrap();
+        let moved = form.fields().iter().map(|f| by_field[&f.id].as_str()).collect::<Vec<_>>();
+        assert_eq!(moved, ["b", "c", "d", "e", "a"]);
+    }
+
+    #[test]
+    fn legacy_fields_get_stable_ids() {
+        let mut json = serde_json::to_value(create_form()).unwrap();
+        for field in json["fields"].as_array_mut().unwrap() {
+            field.as_object_mut().unwrap().remove("id");
+        }
+        let value = Value::BulkString(json.to_string().into_bytes());
+
+        let first = Form::from_redis_value(&value).unwrap();
+        let second = Form::from_redis_value(&value).unwrap();
+        let ids = |form: &Form| form.fields().iter().map(|f| f.id).collect::<Vec<_>>();
+        assert_eq!(ids(&first), ids(&second));
+        assert!(!ids(&first).contains(&FieldId::default()));
+        assert_eq!(ids(&first).iter().collect::<std::collections::HashSet<_>>().len(), 5);
+    }
+
+    #[test]
+    fn cooldown_expiry() {
+        let now = utc("2024-05-01T12:00:00Z");
+        let mut form = create_form();
+        assert_eq!(form.cooldown_expiry(now), None);
+
+        form.set_cooldown(Some(Duration::from_secs(60)));
+        assert_eq!(form.cooldown_expiry(now), Some(utc("2024-05-01T12:01:00Z")));
+
+        form.set_calendar_cooldown(Some(calendar(ResetPeriod::Daily, "00:00", Tz::UTC)));
+        assert_eq!(form.cooldown_expiry(now), Some(utc("2024-05-02T00:00:00Z")));
+    }
+
+    #[test]
+    fn cooldown_modes_are_exclusive() {
+        let mut form = create_form();
+        form.set_cooldown(Some(Duration::from_secs(60)));
+        form.set_calendar_cooldown(Some(calendar(ResetPeriod::Daily, "00:00", Tz::UTC)));
+        assert_eq!(form.cooldown(), None);
+
+        form.set_cooldown(Some(Duration::from_secs(60)));
+        assert_eq!(form.calendar_cooldown(), None);
+    }
+
+    #[test]
+    fn input_length_follows_style() {
+        let input = |field: &FormField| serde_json::to_value(field.input_text("id", "en-US", None, true)).unwrap();
+
+        let mut field = FormField::new("Field".to_owned(), InputTextStyle::Short).unwrap();
+        assert_eq!(input(&field)["max_length"], 1024);
+
+        field.min_length = Some(10);
+        field.max_length = Some(100);
+        assert_eq!(input(&field)["min_length"], 10);
+        assert_eq!(input(&field)["max_length"], 100);
+
+        field.max_length = Some(4000);
+        assert_eq!(input(&field)["max_length"], 1024);
+
+        field.style = InputTextStyle::Paragraph;
+        assert_eq!(input(&field)["max_length"], 4000);
+
+        field.max_length = None;
+        assert_eq!(input(&field)["max_length"], 4000);
+    }
+
+    #[test]
+    fn pages_and_conditions() {
+        let mut form = create_form();
+        assert_eq!(form.page_count(), 1);
+        assert!(form.is_single_page());
+
+        // Fields 3 and 4 go on the second page, followed by a third page with a new field
+        assert_eq!(form.set_field_page(3, 1), Ok(true));
+        assert_eq!(form.set_field_page(3, 1), Ok(true));
+        let mut field = FormField::new("Field 5".to_owned(), InputTextStyle::Short).unwrap();
+        field.set_page(2);
+        form.add_field(field, Some(0)).unwrap();
+        assert_eq!(form.fields().iter().map(|f| f.page()).collect::<Vec<_>>(), vec![0, 0, 0, 1, 1, 2]);
+        assert_eq!(form.fields()[5].name(), "Field 5");
+
+        assert_eq!(form.set_field_condition(0, Some((ConditionEffect::Show, 4, ConditionOperator::Equals, "x".to_owned()))), Err(ConditionError::NotOnEarlierPage));
+        assert_eq!(form.set_field_condition(3, Some((ConditionEffect::Show, 9, ConditionOperator::Equals, "x".to_owned()))), Err(ConditionError::UnknownField));
+        assert_eq!(form.set_field_condition(3, Some((ConditionEffect::Show, 0, ConditionOperator::Equals, "Bug".to_owned()))), Ok(true));
+        assert_eq!(form.set_field_condition(4, Some((ConditionEffect::Require, 1, ConditionOperator::Contains, "yes".to_owned()))), Ok(true));
+        assert_eq!(form.set_page_condition(2, Some((0, ConditionOperator::NotEquals, "bug".to_owned()))), Ok(()));
+        assert!(!form.is_single_page());
+
+        let bug = answers(&form, &[(0, " bug "), (1, "Oh yes")]);
+        assert!(form.is_shown(3, &bug));
+        assert!(form.is_required(&form.fields()[4], &bug));
+        assert_eq!(form.next_page(Some(0), &bug), Some(1));
+        assert_eq!(form.next_page(Some(1), &bug), None);
+        assert!(!form.answered_before(1, &bug));
+        assert!(form.answered_before(1, &answers(&form, &[(0, "bug"), (1, "yes"), (2, "z")])));
+        assert!(form.answered_before(0, &HashMap::new()));
+
+        let other = answers(&form, &[(0, "Feature"), (1, "no")]);
+        assert!(!form.is_shown(3, &other));
+        assert!(!form.is_required(&form.fields()[4], &other));
+        assert_eq!(form.next_page(Some(1), &other), Some(2));
+
+        let mut all = other.clone();
+        all.insert(form_field_id(&form, 3), "hidden".to_owned());
+        all.insert(form_field_id(&form, 5), "shown".to_owned());
+        assert_eq!(form.ordered_answers(&all), vec!["Feature", "no", "", "", "", "shown"]);
+
+        // Field 0 cannot move past the fields depending on it, and removing it drops the conditions
+        assert_eq!(form.set_field_page(0, 1), Err(PageError::BreaksCondition));
+        assert_eq!(form.fields()[0].page(), 0);
+        assert!(form.remove_field(0));
+        assert!(form.fields()[2].condition().is_none());
+        assert!(form.page_conditions().is_empty());
+    }
+
+    #[test]
+    fn pages_hold_five_fields() {
+        let mut form = create_form();
+        let field = FormField::new("Field 5".to_owned(), InputTextStyle::Short).unwrap();
+        assert_eq!(form.add_field(field.clone(), None), Err(AddFieldError::TooManyFields));
+
+        let mut field = field;
+        field.set_page(1);
+        assert_eq!(form.add_field(field, None), Ok(()));
+        assert_eq!(form.set_field_page(0, 1), Ok(true));
+        assert_eq!(form.set_field_page(1, 0), Ok(true));
+        assert_eq!(form.page_count(), 2);
+    }
+
+    fn form_field_id(form: &Form, index: usize) -> FieldId {
+        form.fields()[index].id()
+    }
+
+    fn answers(form: &Form, answers: &[(usize, &str)]) -> HashMap<FieldId, String> {
+        answers.iter().map(|(i, a)| (form_field_id(form, *i), (*a).to_owned())).collect()
+    }
+
+    #[test]
+    fn quizzes_are_graded() {
+        let mut form = create_form();
+        let answers = ["Paris", " london ", "Red", "x", "y"].map(str::to_owned);
+        assert_eq!(form.grade(&answers), None);
+
+        form.fields_mut()[0].set_quiz(Some(QuizAnswer { answers: vec!["paris".to_owned()], points: 2 })).unwrap();
+        form.fields_mut()[1].set_quiz(Some(QuizAnswer { answers: vec!["London".to_owned(), "Londres".to_owned()], points: 1 })).unwrap();
+        form.fields_mut()[2].set_quiz(Some(QuizAnswer { answers: vec!["Blue".to_owned()], points: 3 })).unwrap();
+        assert_eq!(form.grade(&answers), Some(QuizResult { points: 3, max: 6, passed: None, decided: false }));
+
+        form.quiz = Some(QuizSettings { threshold: 3, auto_decide: true, pass_role: None });
+        assert_eq!(form.grade(&answers).and_then(|r| r.passed), Some(true));
+        form.quiz = Some(QuizSettings { threshold: 4, auto_decide: true, pass_role: None });
+        assert_eq!(form.grade(&answers).and_then(|r| r.passed), Some(false));
+
+        // Hidden questions neither earn nor cost points
+        for i in (2..5).rev() {
+            form.set_field_page(i, 1).unwrap();
+        }
+        form.set_field_condition(2, Some((ConditionEffect::Show, 0, ConditionOperator::Equals, "Rome".to_owned()))).unwrap();
+        assert_eq!(form.grade(&answers).map(|r| (r.points, r.max)), Some((3, 3)));
+
+        form.fields_mut()[3].style = InputTextStyle::Paragraph;
+        assert!(form.fields_mut()[3].set_quiz(Some(QuizAnswer { answers: vec!["x".to_owned()], points: 1 })).is_err());
+        assert!(form.fields_mut()[3].set_quiz(None).is_ok());
+    }
+
+    #[test]
+    fn ticket_numbers_are_padded() {
+        assert_eq!(format_ticket(1), "#0001");
+        assert_eq!(format_ticket(42), "#0042");
+        assert_eq!(format_ticket(12345), "#12345");
+    }
+
     fn create_form() -> Form {
         let mut form = Form::new("My Title".to_owned(), ChannelId::new(123)).unwrap();
         form.add_field(FormField::new("Field 0".to_owned(), InputTextStyle::Short).unwrap(), None).unwrap();
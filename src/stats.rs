@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use poise::serenity_prelude::Timestamp;
+
+use crate::state::{FormCounters, SubmissionRecord};
+
+const DAY_SECONDS: i64 = 24 * 60 * 60;
+
+/// How a form has been used, computed from its submissions and counters.
+#[derive(Debug, PartialEq)]
+pub struct FormStats {
+    pub total: usize,
+    pub last_day: usize,
+    pub last_week: usize,
+    /// The average number of submissions per day since the first one.
+    pub per_day: f64,
+    pub unique_submitters: usize,
+    /// The submissions which passed an automatically decided quiz and all which were decided, if
+    /// any were. Staff only close submissions, without accepting or denying them.
+    pub quiz_passed: Option<(usize, usize)>,
+    /// The median time until staff closed a submission.
+    pub median_decision_time: Option<Duration>,
+    pub cooldown_rejections: u64,
+    /// The modals which were closed without submitting and all which were opened, if any were.
+    pub abandoned: Option<(u64, u64)>,
+}
+
+impl FormStats {
+    /// Computes the statistics of the submissions to a single form.
+    pub fn new(records: &[SubmissionRecord], counters: FormCounters, now: Timestamp) -> Self {
+        let now = now.unix_timestamp();
+        let submitted_since = |seconds: i64| records.iter().filter(|r| now - r.submission.submitted_at.unix_timestamp() < seconds).count();
+
+        let first = records.iter().map(|r| r.submission.submitted_at.unix_timestamp()).min();
+        let days = first.map_or(1.0, |first| ((now - first) as f64 / DAY_SECONDS as f64).max(1.0));
+
+        let decisions = records.iter()
+            .filter_map(|r| r.submission.quiz.filter(|q| q.decided).and_then(|q| q.passed))
+            .collect::<Vec<_>>();
+
+        // Submissions decided by a quiz are closed right away and would hide how long staff take
+        let mut decision_times = records.iter()
+            .filter(|r| !r.submission.quiz.is_some_and(|q| q.decided))
+            .filter_map(|r| Some(r.closed_at?.unix_timestamp() - r.submission.submitted_at.unix_timestamp()))
+            .map(|seconds| seconds.max(0) as u64)
+            .collect::<Vec<_>>();
+        decision_times.sort_unstable();
+
+        Self {
+            total: records.len(),
+            last_day: submitted_since(DAY_SECONDS),
+            last_week: submitted_since(7 * DAY_SECONDS),
+            per_day: records.len() as f64 / days,
+            unique_submitters: records.iter().map(|r| r.submission.user_id).collect::<HashSet<_>>().len(),
+            quiz_passed: Some((decisions.iter().filter(|passed| **passed).count(), decisions.len())).filter(|(_, total)| *total > 0),
+            median_decision_time: median(&decision_times).map(Duration::from_secs),
+            cooldown_rejections: counters.cooldown_rejections,
+            // Counting started at different times for some forms, so there may be more submissions than opened modals
+            abandoned: Some((counters.opened.saturating_sub(counters.submitted), counters.opened)).filter(|(_, opened)| *opened > 0),
+        }
+    }
+}
+
+fn median(sorted: &[u64]) -> Option<u64> {
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 0 => Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2),
+        n => Some(sorted[n / 2]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::{ChannelId, GuildId, MessageId, Timestamp, UserId};
+
+    use crate::state::{FormCounters, QuizResult, Submission, SubmissionId, SubmissionRecord};
+
+    use super::FormStats;
+
+    fn record(user: u64, days_ago: i64, passed: Option<bool>, closed_after: Option<i64>) -> SubmissionRecord {
+        let now = Timestamp::from_unix_timestamp(100 * 86400).unwrap();
+        let submitted_at = Timestamp::from_unix_timestamp(now.unix_timestamp() - days_ago * 86400).unwrap();
+        SubmissionRecord {
+            submission: Submission {
+                id: SubmissionId::new(),
+                guild_id: GuildId::new(1),
+                form_id: "00000000-0000-0000-0000-000000000000".parse().unwrap(),
+                user_id: UserId::new(user),
+                user_name: "User".to_owned(),
+                avatar_url: String::new(),
+                locale: "en-US".to_owned(),
+                guild_locale: None,
+                answers: vec![],
+                submitted_at,
+                revision: 0,
+                anonymous: false,
+                number: None,
+                relay: false,
+                quiz: passed.map(|passed| QuizResult { points: 0, max: 0, passed: Some(passed), decided: true }),
+                flagged: false,
+            },
+            thread_id: ChannelId::new(1),
+            message_id: MessageId::new(1),
+            history: vec![],
+            withdrawn: false,
+            closed: closed_after.is_some(),
+            closed_at: closed_after.map(|seconds| Timestamp::from_unix_timestamp(submitted_at.unix_timestamp() + seconds).unwrap()),
+        }
+    }
+
+    #[test]
+    fn stats_are_computed_from_records() {
+        let now = Timestamp::from_unix_timestamp(100 * 86400).unwrap();
+        let records = [
+            record(1, 0, Some(true), Some(0)),
+            record(1, 3, Some(false), Some(0)),
+            record(2, 9, Some(true), Some(0)),
+            record(3, 20, None, Some(600)),
+            record(3, 15, None, Some(60)),
+            record(4, 2, None, None),
+        ];
+        let counters = FormCounters { opened: 10, submitted: 6, cooldown_rejections: 2 };
+        let stats = FormStats::new(&records, counters, now);
+
+        assert_eq!(stats.total, 6);
+        assert_eq!(stats.last_day, 1);
+        assert_eq!(stats.last_week, 3);
+        assert_eq!(stats.per_day, 0.3);
+        assert_eq!(stats.unique_submitters, 4);
+        assert_eq!(stats.quiz_passed, Some((2, 3)));
+        assert_eq!(stats.median_decision_time.map(|d| d.as_secs()), Some(330));
+        assert_eq!(stats.cooldown_rejections, 2);
+        assert_eq!(stats.abandoned, Some((4, 10)));
+
+        let stats = FormStats::new(&[], Default::default(), now);
+        assert_eq!((stats.total, stats.per_day, stats.quiz_passed, stats.median_decision_time, stats.abandoned), (0, 0.0, None, None, None));
+    }
+}
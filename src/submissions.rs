@@ -0,0 +1,182 @@
+use poise::serenity_prelude::*;
+
+use crate::{Error, FrameworkContext, filter};
+use crate::filter::{Incident, IncidentKind};
+use crate::event_handler::ephemeral;
+use crate::i18n::{resolve, tr, tr_args, user_language};
+use crate::modals::answers;
+use crate::responses::{EDIT_CUSTOM_ID_PREFIX, edit_submission_message, guild_locale, truncate_answer, WITHDRAW_CUSTOM_ID_PREFIX};
+use crate::state::{FilterAction, Form, State, SubmissionEdit, SubmissionRecord};
+
+/// How much of each answer is shown when listing changes, so that both fit in one embed field.
+const CHANGE_MAX_LENGTH: usize = 400;
+
+/// Finds a submission which the user may still edit or withdraw; otherwise returns why not.
+async fn own_submission(state: &State, guild_id: GuildId, user_id: UserId, id: &str) -> Result<Result<SubmissionRecord, &'static str>, Error> {
+    let Some(record) = (match id.parse() {
+        Ok(id) => state.get_submission(guild_id, id).await?,
+        Err(_) => None,
+    }) else {
+        return Ok(Err("submission-not-found"));
+    };
+
+    if record.submission.user_id != user_id {
+        return Ok(Err("not-your-submission"));
+    }
+
+    if record.withdrawn {
+        return Ok(Err("submission-already-withdrawn"));
+    }
+
+    if record.closed {
+        return Ok(Err("submission-already-closed"));
+    }
+
+    Ok(Ok(record))
+}
+
+/// Finds the form of a submission, as long as the answers still match up with its fields.
+async fn submission_form(state: &State, record: &SubmissionRecord) -> Result<Result<Form, &'static str>, Error> {
+    match state.get_form(record.submission.form_ref()).await? {
+        Some(form) if form.revision() == record.submission.revision => Ok(Ok(form)),
+        Some(_) => Ok(Err("submission-form-changed")),
+        None => Ok(Err("form-no-longer-exists")),
+    }
+}
+
+/// Builds an embed listing the answers which were changed by an edit.
+fn changes_embed(form: &Form, previous: &[String], current: &[String], guild_locale: &str) -> CreateEmbed {
+    let language = resolve([Some(guild_locale)]);
+    form.fields().iter().zip(previous.iter().zip(current))
+        .filter(|(_, (old, new))| old != new)
+        .fold(CreateEmbed::new().title(tr(language, "submission-changes")).timestamp(Timestamp::now()), |embed, (field, (old, new))| {
+            let change = tr_args(language, "submission-change", &[("old", &truncate_answer(old, CHANGE_MAX_LENGTH)), ("new", &truncate_answer(new, CHANGE_MAX_LENGTH))]);
+            embed.field(field.localized_name(guild_locale), change, false)
+        })
+}
+
+/// Handles the "Edit answers" and "Withdraw" buttons of posted submissions.
+/// Returns whether the interaction was one of them.
+pub async fn handle_button(ctx: &Context, interaction: &ComponentInteraction, guild_id: GuildId, framework: FrameworkContext<'_>) -> Result<bool, Error> {
+    let custom_id = interaction.data.custom_id.as_str();
+    let (id, withdraw) = if let Some(id) = custom_id.strip_prefix(EDIT_CUSTOM_ID_PREFIX) {
+        (id, false)
+    } else if let Some(id) = custom_id.strip_prefix(WITHDRAW_CUSTOM_ID_PREFIX) {
+        (id, true)
+    } else {
+        return Ok(false);
+    };
+
+    let state = framework.user_data;
+    let language = user_language(state, Some(guild_id), &interaction.locale, interaction.guild_locale.as_deref()).await?;
+
+    let mut record = match own_submission(state, guild_id, interaction.user.id, id).await? {
+        Ok(record) => record,
+        Err(key) => {
+            interaction.create_response(ctx, ephemeral(tr(language, key))).await?;
+            return Ok(true);
+        }
+    };
+
+    if withdraw {
+        interaction.defer_ephemeral(ctx).await?;
+        let _in_flight = state.shutdown.track();
+
+        record.withdrawn = true;
+        state.save_submission(&record).await?;
+
+        let guild_locale = guild_locale(state, &record.submission).await?;
+        let form = state.get_form(record.submission.form_ref()).await?;
+        record.thread_id.edit_message(ctx, record.message_id, edit_submission_message(form.as_ref(), &record, &guild_locale)).await?;
+
+        let notice = tr_args(resolve([Some(guild_locale.as_str())]), "submission-withdrawn-notice", &[("user", &record.submission.user_id.mention())]);
+        record.thread_id.send_message(ctx, CreateMessage::new().content(notice)).await?;
+        record.thread_id.edit_thread(ctx, EditThread::new().archived(true)).await?;
+
+        interaction.edit_response(ctx, EditInteractionResponse::new().content(tr(language, "submission-withdrawn"))).await?;
+        return Ok(true);
+    }
+
+    let form = match submission_form(state, &record).await? {
+        Ok(form) => form,
+        Err(key) => {
+            interaction.create_response(ctx, ephemeral(tr(language, key))).await?;
+            return Ok(true);
+        }
+    };
+
+    let answers = form.answers_by_field(&record.submission.answers);
+    // The answers to forms with pages or conditions cannot be edited in a single modal
+    let Some(modal) = form.custom_modal(custom_id.to_owned(), &interaction.locale, &answers) else {
+        interaction.create_response(ctx, ephemeral(tr(language, "submission-not-editable"))).await?;
+        return Ok(true);
+    };
+    interaction.create_response(ctx, CreateInteractionResponse::Modal(modal)).await?;
+    Ok(true)
+}
+
+/// Handles the modal for editing the answers of a posted submission.
+/// Returns whether the interaction was one.
+pub async fn handle_edit(ctx: &Context, interaction: &ModalInteraction, guild_id: GuildId, framework: FrameworkContext<'_>) -> Result<bool, Error> {
+    let Some(id) = interaction.data.custom_id.strip_prefix(EDIT_CUSTOM_ID_PREFIX) else {
+        return Ok(false);
+    };
+
+    let state = framework.user_data;
+    let language = user_language(state, Some(guild_id), &interaction.locale, interaction.guild_locale.as_deref()).await?;
+
+    let record = own_submission(state, guild_id, interaction.user.id, id).await?;
+    let form = match &record {
+        Ok(record) => submission_form(state, record).await?,
+        Err(key) => Err(*key),
+    };
+    let (mut record, form) = match (record, form) {
+        (Ok(record), Ok(form)) => (record, form),
+        (Err(key), _) | (_, Err(key)) => {
+            interaction.create_response(ctx, ephemeral(tr(language, key))).await?;
+            return Ok(true);
+        }
+    };
+
+    let mut answers = answers(&interaction.data);
+    if answers == record.submission.answers {
+        interaction.create_response(ctx, ephemeral(tr(language, "submission-unchanged"))).await?;
+        return Ok(true);
+    }
+
+    let settings = state.get_guild_settings(guild_id).await?;
+    if let Some(found) = filter::check_answers(&[&settings.word_filter, &form.word_filter], &answers) {
+        let guild_locale = guild_locale(state, &record.submission).await?;
+        let incident = Incident { kind: IncidentKind::Submission, user: &interaction.user, guild_locale: &guild_locale, found: &found };
+        filter::report_incident(&ctx.http, state, &settings, &form, incident).await?;
+        if found.action == FilterAction::Reject {
+            interaction.create_response(ctx, ephemeral(tr(language, "submission-blocked"))).await?;
+            return Ok(true);
+        }
+        answers = found.redacted;
+        record.submission.flagged = true;
+    }
+
+    interaction.defer_ephemeral(ctx).await?;
+    let _in_flight = state.shutdown.track();
+
+    // Only submissions which were not decided automatically can be edited, so they are simply scored again
+    record.submission.quiz = form.grade(&answers);
+    let previous_answers = std::mem::replace(&mut record.submission.answers, answers);
+    record.history.push(SubmissionEdit { edited_at: Timestamp::now(), previous_answers });
+    state.save_submission(&record).await?;
+
+    let guild_locale = guild_locale(state, &record.submission).await?;
+    let previous_answers = &record.history.last().expect("edit was just added").previous_answers;
+    // Sending the changes first unarchives the thread, so the submission can be edited
+    let changes = changes_embed(&form, previous_answers, &record.submission.answers, &guild_locale);
+    record.thread_id.send_message(ctx, CreateMessage::new().embed(changes)).await?;
+    record.thread_id.edit_message(ctx, record.message_id, edit_submission_message(Some(&form), &record, &guild_locale)).await?;
+
+    if form.prefill {
+        state.save_last_answers(record.submission.form_ref(), record.submission.user_id, &form.answers_by_field(&record.submission.answers)).await?;
+    }
+
+    interaction.edit_response(ctx, EditInteractionResponse::new().content(tr(language, "submission-edited"))).await?;
+    Ok(true)
+}
@@ -0,0 +1,245 @@
+use std::fmt::Write;
+
+use chrono::DateTime;
+use poise::serenity_prelude::*;
+
+use crate::{Error, FrameworkContext};
+use crate::event_handler::ephemeral;
+use crate::i18n::{resolve, tr, tr_args, user_language};
+use crate::responses::{CLOSE_CUSTOM_ID_PREFIX, edit_submission_message, guild_locale, quiz_summary};
+use crate::state::{Form, Submission, SubmissionRecord};
+
+/// How many messages Discord returns at most per request.
+const MESSAGES_PAGE_SIZE: u8 = 100;
+
+fn format_time(timestamp: Timestamp) -> String {
+    DateTime::from_timestamp(timestamp.unix_timestamp(), 0)
+        .map_or_else(|| timestamp.to_string(), |t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+}
+
+/// Fetches every message of a thread, oldest first.
+async fn thread_messages(http: &Http, thread_id: ChannelId) -> Result<Vec<Message>, Error> {
+    let mut messages = vec![];
+    let mut after = MessageId::new(1);
+
+    loop {
+        let mut page = thread_id.messages(http, GetMessages::new().after(after).limit(MESSAGES_PAGE_SIZE)).await?;
+        let done = page.len() < MESSAGES_PAGE_SIZE as usize;
+        page.sort_by_key(|m| m.id);
+        if let Some(last) = page.last() {
+            after = last.id;
+        }
+        messages.extend(page);
+
+        if done {
+            return Ok(messages);
+        }
+    }
+}
+
+/// Lists the answers of a submission; they are only numbered if the form's fields have changed since.
+fn answers_section(form: Option<&Form>, submission: &Submission, guild_locale: &str) -> String {
+    let fields = form.filter(|f| f.revision() == submission.revision).map(Form::fields);
+    let mut section = String::new();
+    for (i, answer) in submission.answers.iter().enumerate() {
+        let name = fields.and_then(|f| f.get(i)).map_or_else(|| (i + 1).to_string(), |f| f.localized_name(guild_locale).to_owned());
+        let _ = writeln!(section, "{name}:\n{}\n", if answer.is_empty() { "-" } else { answer });
+    }
+    section
+}
+
+fn write_message(transcript: &mut String, message: &Message) {
+    let author = message.member.as_ref().and_then(|m| m.nick.as_deref())
+        .or(message.author.global_name.as_deref())
+        .unwrap_or(&message.author.name);
+    let _ = writeln!(transcript, "[{}] {author}: {}", format_time(message.timestamp), message.content);
+
+    for embed in &message.embeds {
+        let heading = embed.author.as_ref().map(|a| a.name.as_str()).or(embed.title.as_deref());
+        let text = match (heading, embed.description.as_deref()) {
+            (Some(heading), Some(description)) => Some(format!("{heading}: {description}")),
+            (Some(text), None) | (None, Some(text)) => Some(text.to_owned()),
+            (None, None) => None,
+        };
+        if let Some(text) = text {
+            let _ = writeln!(transcript, "    > {text}");
+        }
+        for field in &embed.fields {
+            let _ = writeln!(transcript, "    > {}: {}", field.name, field.value);
+        }
+    }
+
+    for attachment in &message.attachments {
+        let _ = writeln!(transcript, "    {}", attachment.url);
+    }
+}
+
+/// Builds a plain text transcript of the answers of a submission, without the thread.
+fn answers_transcript(form: Option<&Form>, record: &SubmissionRecord, guild_locale: &str, closed_by: &User) -> String {
+    let language = resolve([Some(guild_locale)]);
+    let submission = &record.submission;
+
+    let title = form.map_or_else(|| tr(language, "deleted-form"), |f| f.localized_title(guild_locale).to_owned());
+    let submitter = if submission.anonymous {
+        tr(language, "anonymous-user")
+    } else {
+        format!("{} ({})", submission.user_name, submission.user_id)
+    };
+
+    let mut transcript = match submission.ticket() {
+        Some(ticket) => format!("{ticket} {title}\n"),
+        None => title + "\n",
+    };
+    transcript += &tr_args(language, "transcript-submitted", &[("user", &submitter), ("time", &format_time(submission.submitted_at))]);
+    transcript += "\n";
+    if let Some(result) = &submission.quiz {
+        transcript += &quiz_summary(language, result);
+        transcript += "\n";
+    }
+    transcript += &tr_args(language, "transcript-closed", &[("user", &closed_by.name), ("time", &format_time(Timestamp::now()))]);
+    transcript += "\n\n";
+
+    let _ = writeln!(transcript, "== {} ==\n", tr(language, "transcript-answers"));
+    transcript += &answers_section(form, submission, guild_locale);
+    transcript
+}
+
+/// Builds a plain text transcript of the answers and every message in the thread of a submission.
+async fn transcript(http: &Http, answers_transcript: &str, record: &SubmissionRecord, guild_locale: &str) -> Result<String, Error> {
+    let language = resolve([Some(guild_locale)]);
+    let mut transcript = answers_transcript.to_owned();
+    let _ = writeln!(transcript, "== {} ==\n", tr(language, "transcript-messages"));
+    // The answers are already listed above
+    for message in thread_messages(http, record.thread_id).await?.iter().filter(|m| m.id != record.message_id) {
+        write_message(&mut transcript, message);
+    }
+
+    Ok(transcript)
+}
+
+/// Handles the "Close" button of posted submissions: locks and archives the thread and sends a
+/// transcript to the transcript channel and, if enabled, the submitter.
+/// Returns whether the interaction was one.
+pub async fn handle_close(ctx: &Context, interaction: &ComponentInteraction, guild_id: GuildId, framework: FrameworkContext<'_>) -> Result<bool, Error> {
+    let Some(id) = interaction.data.custom_id.strip_prefix(CLOSE_CUSTOM_ID_PREFIX) else {
+        return Ok(false);
+    };
+
+    let state = framework.user_data;
+    let language = user_language(state, Some(guild_id), &interaction.locale, interaction.guild_locale.as_deref()).await?;
+
+    if !interaction.member.as_ref().and_then(|m| m.permissions).is_some_and(|p| p.manage_channels()) {
+        interaction.create_response(ctx, ephemeral(tr(language, "close-requires-staff"))).await?;
+        return Ok(true);
+    }
+
+    let record = match id.parse() {
+        Ok(id) => state.get_submission(guild_id, id).await?,
+        Err(_) => None,
+    };
+    let mut record = match record {
+        Some(record) if record.closed => {
+            interaction.create_response(ctx, ephemeral(tr(language, "submission-already-closed"))).await?;
+            return Ok(true);
+        }
+        Some(record) => record,
+        None => {
+            interaction.create_response(ctx, ephemeral(tr(language, "submission-not-found"))).await?;
+            return Ok(true);
+        }
+    };
+
+    interaction.defer_ephemeral(ctx).await?;
+    let _in_flight = state.shutdown.track();
+
+    let guild_locale = guild_locale(state, &record.submission).await?;
+    let guild_language = resolve([Some(guild_locale.as_str())]);
+    let form = state.get_form(record.submission.form_ref()).await?;
+
+    let answers_transcript = answers_transcript(form.as_ref(), &record, &guild_locale, &interaction.user);
+    let transcript = transcript(&ctx.http, &answers_transcript, &record, &guild_locale).await?;
+
+    let open_record = record.clone();
+    record.closed = true;
+    record.closed_at = Some(Timestamp::now());
+    let notice = tr_args(guild_language, "submission-closed-notice", &[("user", &interaction.user.mention())]);
+    let closed = async {
+        record.thread_id.edit_message(ctx, record.message_id, edit_submission_message(form.as_ref(), &record, &guild_locale)).await?;
+        record.thread_id.send_message(ctx, CreateMessage::new().content(notice)).await?;
+        record.thread_id.edit_thread(ctx, EditThread::new().locked(true).archived(true)).await
+    }.await;
+    if let Err(e) = closed {
+        // Bring the Close button back, so closing can be tried again
+        let edit_message = edit_submission_message(form.as_ref(), &open_record, &guild_locale);
+        if let Err(e) = record.thread_id.edit_message(ctx, record.message_id, edit_message).await {
+            tracing::warn!(error = ?e, id = %record.submission.id, "Failed to restore submission after closing failed");
+        }
+        return Err(e.into());
+    }
+    // Only saved once the thread is locked, so a failed attempt leaves the submission open
+    state.save_submission(&record).await?;
+
+    let file_name = format!("transcript-{}.txt", record.submission.number.map_or_else(|| record.submission.id.to_string(), |n| n.to_string()));
+    let settings = state.get_guild_settings(guild_id).await?;
+    if let Some(channel) = settings.transcript_channel {
+        let content = tr_args(guild_language, "transcript-posted", &[("thread", &record.thread_id.mention()), ("user", &interaction.user.mention())]);
+        let message = CreateMessage::new().content(content).add_file(CreateAttachment::bytes(transcript.clone(), file_name.clone()));
+        channel.send_message(ctx, message).await?;
+    }
+
+    if settings.transcript_dm {
+        let submitter_language = resolve([Some(record.submission.locale.as_str())]);
+        // Submitters who were kept out of the thread must not see what staff wrote in it; they
+        // already have the messages relayed to them in their DMs
+        let transcript = if record.submission.in_thread() { transcript } else { answers_transcript };
+        let message = CreateMessage::new()
+            .content(tr(submitter_language, "transcript-dm"))
+            .add_file(CreateAttachment::bytes(transcript, file_name));
+        if let Err(e) = record.submission.user_id.direct_message(ctx, message).await {
+            tracing::warn!(error = ?e, id = %record.submission.id, "Failed to send transcript to submitter");
+        }
+    }
+
+    interaction.edit_response(ctx, EditInteractionResponse::new().content(tr(language, "submission-closed"))).await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::{ChannelId, GuildId, InputTextStyle, Timestamp, UserId};
+
+    use crate::state::{Form, FormField, Submission, SubmissionId};
+
+    use super::answers_section;
+
+    #[test]
+    fn answers_are_listed_by_field() {
+        let mut form = Form::new("Title".to_owned(), ChannelId::new(1)).unwrap();
+        form.add_field(FormField::new("Name".to_owned(), InputTextStyle::Short).unwrap(), None).unwrap();
+        form.add_field(FormField::new("Reason".to_owned(), InputTextStyle::Paragraph).unwrap(), None).unwrap();
+
+        let mut submission = Submission {
+            id: SubmissionId::new(),
+            guild_id: GuildId::new(1),
+            form_id: form.id(),
+            user_id: UserId::new(1),
+            user_name: "User".to_owned(),
+            avatar_url: String::new(),
+            locale: "en-US".to_owned(),
+            guild_locale: None,
+            answers: vec!["Bob".to_owned(), String::new()],
+            submitted_at: Timestamp::now(),
+            revision: form.revision(),
+            anonymous: false,
+            number: Some(1),
+            relay: false,
+            quiz: None,
+            flagged: false,
+        };
+        assert_eq!(answers_section(Some(&form), &submission, "en-US"), "Name:\nBob\n\nReason:\n-\n\n");
+
+        submission.revision += 1;
+        assert_eq!(answers_section(Some(&form), &submission, "en-US"), "1:\nBob\n\n2:\n-\n\n");
+        assert_eq!(answers_section(None, &submission, "en-US"), "1:\nBob\n\n2:\n-\n\n");
+    }
+}